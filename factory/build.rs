@@ -0,0 +1,50 @@
+//! Builds the vault WASM that the factory tests upload and deploy, so they never run
+//! against a missing or stale binary.
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+const VAULT_TARGET: &str = "wasm32v1-none";
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let vault_dir = manifest_dir.parent().unwrap();
+    println!("cargo:rerun-if-changed={}", vault_dir.join("src").display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        vault_dir.join("Cargo.toml").display()
+    );
+
+    // Only the host-side tests deploy the vault
+    if env::var("TARGET").unwrap().starts_with("wasm32") {
+        return;
+    }
+
+    // A separate target directory keeps the nested build off the outer build's lock, and
+    // the outer build's flags and wrappers (such as clippy's) do not apply to the WASM
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let target_dir = out_dir.join("vault-target");
+    let status = Command::new(env::var("CARGO").unwrap())
+        .args(["build", "--release", "-p", "strategy-vault"])
+        .arg("--target")
+        .arg(VAULT_TARGET)
+        .arg("--manifest-path")
+        .arg(vault_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("RUSTFLAGS")
+        .env_remove("RUSTC_WRAPPER")
+        .env_remove("RUSTC_WORKSPACE_WRAPPER")
+        .status()
+        .expect("failed to run cargo for the vault WASM");
+    assert!(status.success(), "building the vault WASM failed");
+
+    fs::copy(
+        target_dir
+            .join(VAULT_TARGET)
+            .join("release")
+            .join("strategy_vault.wasm"),
+        out_dir.join("strategy_vault.wasm"),
+    )
+    .unwrap();
+}
@@ -0,0 +1,129 @@
+//! Vault Factory - deploys strategy vaults from a stored WASM hash
+//!
+//! Vault addresses are deterministic: they depend only on the factory, the deployer
+//! and a deployer-chosen salt. Every deployed vault is recorded in an on-chain
+//! registry under the underlying asset it reports, so integrators can discover the
+//! vaults of an asset without trusting an off-chain list.
+
+use soroban_sdk::{
+    contract, contractclient, contractevent, contractimpl, xdr::ToXdr, Address, BytesN, Env, Val,
+    Vec,
+};
+
+use crate::storage;
+
+#[contractevent(topics = ["vault_created", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultCreated {
+    #[topic]
+    pub asset: Address,
+    #[topic]
+    pub vault: Address,
+    pub deployer: Address,
+}
+
+#[contractevent(topics = ["wasm_hash_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WasmHashSet {
+    pub wasm_hash: BytesN<32>,
+}
+
+/// Subset of the vault interface read when registering a deployed vault
+#[allow(dead_code)]
+#[contractclient(name = "DeployedVaultClient")]
+pub trait DeployedVault {
+    fn query_asset(e: Env) -> Address;
+}
+
+#[contract]
+pub struct VaultFactory;
+
+#[contractimpl]
+impl VaultFactory {
+    /// Initializes the factory
+    ///
+    /// # Arguments
+    /// * `admin` - Address allowed to change the vault WASM
+    /// * `wasm_hash` - Hash of the uploaded vault WASM that vaults are deployed from
+    pub fn __constructor(e: Env, admin: Address, wasm_hash: BytesN<32>) {
+        storage::set_admin(&e, &admin);
+        storage::set_wasm_hash(&e, &wasm_hash);
+    }
+
+    /// Returns the admin address
+    pub fn admin(e: Env) -> Address {
+        storage::extend_instance(&e);
+        storage::get_admin(&e)
+    }
+
+    /// Returns the hash of the vault WASM that new vaults are deployed from
+    pub fn wasm_hash(e: Env) -> BytesN<32> {
+        storage::extend_instance(&e);
+        storage::get_wasm_hash(&e)
+    }
+
+    /// Admin points the factory at a new vault WASM. Vaults already deployed keep
+    /// their code.
+    pub fn set_wasm_hash(e: Env, wasm_hash: BytesN<32>) {
+        storage::get_admin(&e).require_auth();
+        storage::set_wasm_hash(&e, &wasm_hash);
+        WasmHashSet { wasm_hash }.publish(&e);
+        storage::extend_instance(&e);
+    }
+
+    /// Deploys a vault with `constructor_args`, the vault constructor's arguments in
+    /// order, at the address `vault_address(deployer, salt)` returns, and registers it
+    /// under the asset the new vault reports. Returns the vault's address.
+    pub fn deploy(
+        e: Env,
+        deployer: Address,
+        salt: BytesN<32>,
+        constructor_args: Vec<Val>,
+    ) -> Address {
+        deployer.require_auth();
+        let vault = e
+            .deployer()
+            .with_current_contract(Self::deployer_salt(&e, &deployer, &salt))
+            .deploy_v2(storage::get_wasm_hash(&e), constructor_args);
+        let asset = DeployedVaultClient::new(&e, &vault).query_asset();
+        storage::push_vault(&e, &asset, &vault);
+        VaultCreated {
+            asset,
+            vault: vault.clone(),
+            deployer,
+        }
+        .publish(&e);
+        storage::extend_instance(&e);
+        vault
+    }
+
+    /// Returns the address `deploy` creates for `deployer` and `salt`
+    pub fn vault_address(e: Env, deployer: Address, salt: BytesN<32>) -> Address {
+        storage::extend_instance(&e);
+        e.deployer()
+            .with_current_contract(Self::deployer_salt(&e, &deployer, &salt))
+            .deployed_address()
+    }
+
+    /// Returns the number of vaults deployed for `asset`
+    pub fn vault_count(e: Env, asset: Address) -> u32 {
+        storage::extend_instance(&e);
+        storage::get_vault_count(&e, &asset)
+    }
+
+    /// Returns up to `limit` (at most `MAX_PAGE_SIZE`) of the vaults deployed for
+    /// `asset`, oldest first, starting at index `start`
+    pub fn vaults(e: Env, asset: Address, start: u32, limit: u32) -> Vec<Address> {
+        storage::extend_instance(&e);
+        storage::get_vaults(&e, &asset, start, limit)
+    }
+}
+
+impl VaultFactory {
+    /// Binds the salt to the deployer, so nobody else can take a deployer's address
+    fn deployer_salt(e: &Env, deployer: &Address, salt: &BytesN<32>) -> BytesN<32> {
+        e.crypto()
+            .sha256(&(deployer.clone(), salt.clone()).to_xdr(e))
+            .into()
+    }
+}
@@ -0,0 +1,105 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, BytesN, Env, Vec as SorobanVec};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const INSTANCE_EXTEND_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_THRESHOLD: u32 = INSTANCE_EXTEND_AMOUNT - DAY_IN_LEDGERS;
+const REGISTRY_EXTEND_AMOUNT: u32 = 100 * DAY_IN_LEDGERS;
+const REGISTRY_TTL_THRESHOLD: u32 = REGISTRY_EXTEND_AMOUNT - DAY_IN_LEDGERS;
+
+/// Most vaults `vaults` returns per call
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum FactoryStorageKey {
+    Admin,
+    WasmHash,
+    /// Number of vaults registered for an asset
+    VaultCount(Address),
+    /// The vault registered for an asset at an index, in deployment order
+    Vault(Address, u32),
+}
+
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_EXTEND_AMOUNT);
+}
+
+pub fn get_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<FactoryStorageKey, Address>(&FactoryStorageKey::Admin)
+        .unwrap_optimized()
+}
+
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage()
+        .instance()
+        .set::<FactoryStorageKey, Address>(&FactoryStorageKey::Admin, admin);
+}
+
+/// Returns the hash of the vault WASM that new vaults are deployed from
+pub fn get_wasm_hash(e: &Env) -> BytesN<32> {
+    e.storage()
+        .instance()
+        .get::<FactoryStorageKey, BytesN<32>>(&FactoryStorageKey::WasmHash)
+        .unwrap_optimized()
+}
+
+pub fn set_wasm_hash(e: &Env, wasm_hash: &BytesN<32>) {
+    e.storage()
+        .instance()
+        .set::<FactoryStorageKey, BytesN<32>>(&FactoryStorageKey::WasmHash, wasm_hash);
+}
+
+/// Returns the number of vaults registered for `asset`
+pub fn get_vault_count(e: &Env, asset: &Address) -> u32 {
+    let key = FactoryStorageKey::VaultCount(asset.clone());
+    let result = e.storage().persistent().get::<FactoryStorageKey, u32>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, REGISTRY_TTL_THRESHOLD, REGISTRY_EXTEND_AMOUNT);
+    }
+    result.unwrap_or(0)
+}
+
+/// Returns up to `limit` vaults registered for `asset` from index `start`, oldest
+/// first. `limit` is capped at `MAX_PAGE_SIZE`.
+pub fn get_vaults(e: &Env, asset: &Address, start: u32, limit: u32) -> SorobanVec<Address> {
+    let end = get_vault_count(e, asset).min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+    let mut vaults = SorobanVec::new(e);
+    for index in start..end {
+        let key = FactoryStorageKey::Vault(asset.clone(), index);
+        let vault = e
+            .storage()
+            .persistent()
+            .get::<FactoryStorageKey, Address>(&key)
+            .unwrap_optimized();
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, REGISTRY_TTL_THRESHOLD, REGISTRY_EXTEND_AMOUNT);
+        vaults.push_back(vault);
+    }
+    vaults
+}
+
+/// Registers a vault under its underlying asset at the next index
+pub fn push_vault(e: &Env, asset: &Address, vault: &Address) {
+    let index = get_vault_count(e, asset);
+    let key = FactoryStorageKey::Vault(asset.clone(), index);
+    e.storage()
+        .persistent()
+        .set::<FactoryStorageKey, Address>(&key, vault);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, REGISTRY_TTL_THRESHOLD, REGISTRY_EXTEND_AMOUNT);
+    let count_key = FactoryStorageKey::VaultCount(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<FactoryStorageKey, u32>(&count_key, &(index + 1));
+    e.storage()
+        .persistent()
+        .extend_ttl(&count_key, REGISTRY_TTL_THRESHOLD, REGISTRY_EXTEND_AMOUNT);
+}
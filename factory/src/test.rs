@@ -0,0 +1,213 @@
+#![cfg(test)]
+
+extern crate std;
+
+use soroban_sdk::{
+    contracttype, map,
+    testutils::{Address as _, AuthorizedFunction, Events},
+    xdr::ScVal,
+    Address, BytesN, Env, IntoVal, Map, String, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::{
+    contract::DeployedVaultClient, storage::MAX_PAGE_SIZE, VaultFactory, VaultFactoryClient,
+};
+
+// Built from the vault sources by the factory's build script
+const VAULT_WASM: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/strategy_vault.wasm"));
+
+const SCALAR_7: i128 = 10_000_000;
+
+// Encode like the vault constructor's launch types; these tests only deploy vaults
+// without a seed deposit or deposit cap
+#[contracttype]
+enum LaunchSeed {
+    None,
+}
+
+#[contracttype]
+enum LaunchCap {
+    None,
+}
+
+#[contracttype]
+struct LaunchConfig {
+    seed: LaunchSeed,
+    deposit_cap: LaunchCap,
+}
+
+fn setup_test<'a>() -> (Env, VaultFactoryClient<'a>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let wasm_hash = env.deployer().upload_contract_wasm(VAULT_WASM);
+    let factory = VaultFactoryClient::new(&env, &env.register(VaultFactory, (admin, wasm_hash)));
+    let asset = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    (env, factory, asset)
+}
+
+fn vault_args(env: &Env, asset: &Address) -> Vec<Val> {
+    (
+        Address::generate(env),
+        String::from_str(env, "Vault Shares"),
+        String::from_str(env, "vTKN"),
+        asset.clone(),
+        0u32,
+        Vec::<Address>::new(env),
+        300u64,
+        SCALAR_7,
+        86_400u64,
+        LaunchConfig {
+            seed: LaunchSeed::None,
+            deposit_cap: LaunchCap::None,
+        },
+    )
+        .into_val(env)
+}
+
+#[test]
+fn test_deploy_registers_vault_under_its_asset() {
+    let (env, factory, asset) = setup_test();
+    let deployer = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[1; 32]);
+
+    let expected = factory.vault_address(&deployer, &salt);
+    let vault = factory.deploy(&deployer, &salt, &vault_args(&env, &asset));
+
+    assert_eq!(vault, expected);
+    let (_, topics, data) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, _, _)| *contract == factory.address)
+        .last()
+        .unwrap();
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&env, "vault_created"),
+            Symbol::new(&env, "v1"),
+            asset.clone(),
+            vault.clone(),
+        )
+            .into_val(&env)
+    );
+    let expected_data: Map<Symbol, Val> = map![
+        &env,
+        (Symbol::new(&env, "deployer"), deployer.into_val(&env))
+    ];
+    assert_eq!(
+        ScVal::try_from_val(&env, &data).unwrap(),
+        ScVal::try_from_val(&env, &expected_data.to_val()).unwrap()
+    );
+
+    assert_eq!(DeployedVaultClient::new(&env, &vault).query_asset(), asset);
+    assert_eq!(factory.vault_count(&asset), 1);
+    assert_eq!(
+        factory.vaults(&asset, &0, &10),
+        Vec::from_array(&env, [vault.clone()])
+    );
+}
+
+#[test]
+fn test_registry_lists_vaults_per_asset() {
+    let (env, factory, asset) = setup_test();
+    let other_asset = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let deployer = Address::generate(&env);
+
+    let first = factory.deploy(
+        &deployer,
+        &BytesN::from_array(&env, &[1; 32]),
+        &vault_args(&env, &asset),
+    );
+    let other = factory.deploy(
+        &deployer,
+        &BytesN::from_array(&env, &[2; 32]),
+        &vault_args(&env, &other_asset),
+    );
+    let second = factory.deploy(
+        &deployer,
+        &BytesN::from_array(&env, &[3; 32]),
+        &vault_args(&env, &asset),
+    );
+
+    assert_eq!(
+        factory.vaults(&asset, &0, &10),
+        Vec::from_array(&env, [first, second.clone()])
+    );
+    assert_eq!(
+        factory.vaults(&other_asset, &0, &10),
+        Vec::from_array(&env, [other])
+    );
+    assert_eq!(factory.vault_count(&Address::generate(&env)), 0);
+    assert!(factory.vaults(&Address::generate(&env), &0, &10).is_empty());
+
+    // Pages start at an index and stop at the last vault
+    assert_eq!(
+        factory.vaults(&asset, &1, &10),
+        Vec::from_array(&env, [second])
+    );
+    assert!(factory.vaults(&asset, &2, &10).is_empty());
+    assert!(factory.vaults(&asset, &u32::MAX, &u32::MAX).is_empty());
+}
+
+#[test]
+fn test_vaults_page_size_is_capped() {
+    let (env, factory, asset) = setup_test();
+    let deployer = Address::generate(&env);
+
+    for i in 0..=MAX_PAGE_SIZE {
+        factory.deploy(
+            &deployer,
+            &BytesN::from_array(&env, &[i as u8; 32]),
+            &vault_args(&env, &asset),
+        );
+    }
+
+    assert_eq!(factory.vault_count(&asset), MAX_PAGE_SIZE + 1);
+    assert_eq!(factory.vaults(&asset, &0, &u32::MAX).len(), MAX_PAGE_SIZE);
+    assert_eq!(factory.vaults(&asset, &MAX_PAGE_SIZE, &u32::MAX).len(), 1);
+}
+
+#[test]
+fn test_salt_is_bound_to_deployer() {
+    let (env, factory, asset) = setup_test();
+    let deployer = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[1; 32]);
+
+    let vault = factory.deploy(&deployer, &salt, &vault_args(&env, &asset));
+
+    // Another deployer with the same salt gets a different address
+    assert_ne!(
+        factory.vault_address(&Address::generate(&env), &salt),
+        vault
+    );
+    // The same deployer cannot reuse a salt
+    assert!(factory
+        .try_deploy(&deployer, &salt, &vault_args(&env, &asset))
+        .is_err());
+    assert_eq!(factory.vault_count(&asset), 1);
+}
+
+#[test]
+fn test_admin_sets_wasm_hash() {
+    let (env, factory, _) = setup_test();
+    let wasm_hash = BytesN::from_array(&env, &[9; 32]);
+
+    factory.set_wasm_hash(&wasm_hash);
+
+    let (auth_address, invocation) = &env.auths()[0];
+    assert_eq!(*auth_address, factory.admin());
+    assert!(matches!(
+        &invocation.function,
+        AuthorizedFunction::Contract((contract, name, _))
+            if *contract == factory.address && *name == Symbol::new(&env, "set_wasm_hash")
+    ));
+    assert_eq!(factory.wasm_hash(), wasm_hash);
+}
@@ -0,0 +1,805 @@
+//! Off-chain decoding of vault events
+//!
+//! Decodes the events emitted by the vault from their XDR form into plain Rust
+//! structs, so indexers and bots share one decoder with the contract instead of
+//! re-deriving topic and payload layouts by hand.
+//!
+//! Events defined by this contract carry a schema version (`"v1"`, ...) as their
+//! second topic. Unversioned events are treated as version 0, which also covers the
+//! `deposit`/`withdraw` events emitted by the OpenZeppelin vault. Decoders for every
+//! released version are kept so indexers can replay historical ledgers.
+
+extern crate std;
+
+use soroban_sdk::xdr::{
+    ContractEvent, ContractEventBody, Limits, ReadXdr, ScAddress, ScMap, ScVal,
+};
+
+/// Latest event schema version emitted by the contract
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+/// Errors returned while decoding a vault event
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The XDR bytes could not be parsed as a contract event
+    InvalidXdr,
+    /// The event name topic is missing or not emitted by the vault
+    UnknownEvent,
+    /// The schema version topic is newer than this decoder understands
+    UnsupportedVersion,
+    /// The topics do not match the layout of the named event
+    InvalidTopics,
+    /// The data payload does not match the layout of the named event
+    InvalidData,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositEvent {
+    pub operator: ScAddress,
+    pub from: ScAddress,
+    pub receiver: ScAddress,
+    pub assets: i128,
+    pub shares: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawEvent {
+    pub operator: ScAddress,
+    pub receiver: ScAddress,
+    pub owner: ScAddress,
+    pub assets: i128,
+    pub shares: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyWithdrawEvent {
+    pub strategy: ScAddress,
+    pub amount: i128,
+    /// Cumulative assets withdrawn by the strategy, `None` before v2
+    pub total_sent: Option<i128>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyDepositEvent {
+    pub strategy: ScAddress,
+    pub amount: i128,
+    pub profit_share: i128,
+    /// Cumulative assets returned by the strategy, `None` before v2
+    pub total_received: Option<i128>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyReportEvent {
+    pub strategy: ScAddress,
+    pub gain: i128,
+    pub loss: i128,
+    pub fees: i128,
+    pub debt: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProfitShareSetEvent {
+    pub strategy: ScAddress,
+    pub rate: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BorrowFeeSetEvent {
+    pub strategy: ScAddress,
+    pub rate: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BorrowFeeChargedEvent {
+    pub strategy: ScAddress,
+    pub fee: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyManagerSetEvent {
+    pub strategy: ScAddress,
+    pub manager: Option<ScAddress>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxDeployedRateSetEvent {
+    pub rate: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawLimitSetEvent {
+    pub limit: i128,
+    pub window: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxPriceChangeSetEvent {
+    pub rate: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceOverrideGrantedEvent {
+    pub strategy: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyScheduledEvent {
+    pub strategy: ScAddress,
+    pub activation_time: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyActivatedEvent {
+    pub strategy: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyMigratedEvent {
+    pub old_strategy: ScAddress,
+    pub new_strategy: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExposureCapSetEvent {
+    pub strategy: ScAddress,
+    pub cap: Option<i128>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxDeployedSetEvent {
+    pub cap: Option<i128>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MinSupplySetEvent {
+    pub min_supply: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouncilSetEvent {
+    pub council: Option<ScAddress>,
+    pub threshold: i128,
+    pub window: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdleDeploySetEvent {
+    pub strategy: Option<ScAddress>,
+    pub threshold: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyWithdrawRequestedEvent {
+    pub strategy: ScAddress,
+    pub amount: i128,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockTiersSetEvent {
+    pub tiers: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockExemptionSetEvent {
+    pub account: ScAddress,
+    pub exempt: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyPayoutSetEvent {
+    pub strategy: ScAddress,
+    pub payout: Option<ScAddress>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DonationEvent {
+    pub from: ScAddress,
+    pub amount: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WindDownStartedEvent {
+    pub total_assets: i128,
+    pub deployed: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceDeltaSetEvent {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyHeartbeatEvent {
+    pub strategy: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportIntervalSetEvent {
+    pub interval: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SameLedgerGuardSetEvent {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionMigratedEvent {
+    pub owner: ScAddress,
+    pub new_vault: ScAddress,
+    pub shares: i128,
+    pub assets: i128,
+    pub new_shares: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OperatorSetEvent {
+    pub owner: ScAddress,
+    pub operator: ScAddress,
+    pub approved: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareLockSetEvent {
+    pub owner: ScAddress,
+    pub spender: ScAddress,
+    pub shares: i128,
+    pub expiration_ledger: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareLockReleasedEvent {
+    pub owner: ScAddress,
+    pub spender: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositRootSetEvent {
+    pub root: Option<[u8; 32]>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouponSignerSetEvent {
+    pub signer: Option<[u8; 32]>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernorSetEvent {
+    pub governor: ScAddress,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitFeeSetEvent {
+    pub rate: i128,
+    pub duration: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitFeeChargedEvent {
+    pub owner: ScAddress,
+    pub fee: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiverHookSetEvent {
+    pub receiver: ScAddress,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootstrapSharesLockedEvent {
+    pub depositor: ScAddress,
+    pub shares: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataUpdatedEvent {
+    pub name: std::string::String,
+    pub description: std::string::String,
+    pub uri: std::string::String,
+}
+
+/// A decoded vault event
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VaultEvent {
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    StrategyWithdraw(StrategyWithdrawEvent),
+    StrategyDeposit(StrategyDepositEvent),
+    StrategyReport(StrategyReportEvent),
+    ProfitShareSet(ProfitShareSetEvent),
+    BorrowFeeSet(BorrowFeeSetEvent),
+    BorrowFeeCharged(BorrowFeeChargedEvent),
+    StrategyManagerSet(StrategyManagerSetEvent),
+    MaxDeployedRateSet(MaxDeployedRateSetEvent),
+    WithdrawLimitSet(WithdrawLimitSetEvent),
+    MaxPriceChangeSet(MaxPriceChangeSetEvent),
+    PriceOverrideGranted(PriceOverrideGrantedEvent),
+    StrategyScheduled(StrategyScheduledEvent),
+    StrategyActivated(StrategyActivatedEvent),
+    StrategyMigrated(StrategyMigratedEvent),
+    ExposureCapSet(ExposureCapSetEvent),
+    MaxDeployedSet(MaxDeployedSetEvent),
+    MinSupplySet(MinSupplySetEvent),
+    CouncilSet(CouncilSetEvent),
+    IdleDeploySet(IdleDeploySetEvent),
+    StrategyWithdrawRequested(StrategyWithdrawRequestedEvent),
+    LockTiersSet(LockTiersSetEvent),
+    LockExemptionSet(LockExemptionSetEvent),
+    StrategyPayoutSet(StrategyPayoutSetEvent),
+    Donation(DonationEvent),
+    WindDownStarted(WindDownStartedEvent),
+    BalanceDeltaSet(BalanceDeltaSetEvent),
+    SameLedgerGuardSet(SameLedgerGuardSetEvent),
+    StrategyHeartbeat(StrategyHeartbeatEvent),
+    ReportIntervalSet(ReportIntervalSetEvent),
+    PositionMigrated(PositionMigratedEvent),
+    OperatorSet(OperatorSetEvent),
+    ShareLockSet(ShareLockSetEvent),
+    ShareLockReleased(ShareLockReleasedEvent),
+    DepositRootSet(DepositRootSetEvent),
+    CouponSignerSet(CouponSignerSetEvent),
+    GovernorSet(GovernorSetEvent),
+    ExitFeeSet(ExitFeeSetEvent),
+    ExitFeeCharged(ExitFeeChargedEvent),
+    ReceiverHookSet(ReceiverHookSetEvent),
+    BootstrapSharesLocked(BootstrapSharesLockedEvent),
+    MetadataUpdated(MetadataUpdatedEvent),
+}
+
+/// Decodes an XDR-encoded `ContractEvent`
+pub fn decode_xdr(bytes: &[u8]) -> Result<VaultEvent, DecodeError> {
+    let event =
+        ContractEvent::from_xdr(bytes, Limits::none()).map_err(|_| DecodeError::InvalidXdr)?;
+    decode_contract_event(&event)
+}
+
+/// Decodes a `ContractEvent` emitted by the vault
+pub fn decode_contract_event(event: &ContractEvent) -> Result<VaultEvent, DecodeError> {
+    let ContractEventBody::V0(body) = &event.body;
+    decode(&body.topics, &body.data)
+}
+
+/// Decodes an event from its raw topics and data payload
+pub fn decode(topics: &[ScVal], data: &ScVal) -> Result<VaultEvent, DecodeError> {
+    decode_versioned(topics, data).map(|(_, event)| event)
+}
+
+/// Decodes an event from its raw topics and data payload, returning its schema version
+pub fn decode_versioned(topics: &[ScVal], data: &ScVal) -> Result<(u32, VaultEvent), DecodeError> {
+    let Some(ScVal::Symbol(name)) = topics.first() else {
+        return Err(DecodeError::UnknownEvent);
+    };
+    let (version, topics) = match topics.get(1) {
+        Some(ScVal::Symbol(tag)) => (parse_version(tag.as_slice())?, &topics[2..]),
+        _ => (0, &topics[1..]),
+    };
+    if version > EVENT_SCHEMA_VERSION {
+        return Err(DecodeError::UnsupportedVersion);
+    }
+    // Payloads are identical across v0 and v1. v2 adds cumulative totals to the
+    // strategy flow events.
+    let event = match name.to_utf8_string_lossy().as_str() {
+        "deposit" => {
+            let [operator, from, receiver] = addresses::<3>(topics)?;
+            Ok(VaultEvent::Deposit(DepositEvent {
+                operator,
+                from,
+                receiver,
+                assets: field_i128(data, "assets")?,
+                shares: field_i128(data, "shares")?,
+            }))
+        }
+        "withdraw" => {
+            let [operator, receiver, owner] = addresses::<3>(topics)?;
+            Ok(VaultEvent::Withdraw(WithdrawEvent {
+                operator,
+                receiver,
+                owner,
+                assets: field_i128(data, "assets")?,
+                shares: field_i128(data, "shares")?,
+            }))
+        }
+        "strategy_withdraw" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyWithdraw(StrategyWithdrawEvent {
+                strategy,
+                amount: field_i128(data, "amount")?,
+                total_sent: since_v2(version, || field_i128(data, "total_sent"))?,
+            }))
+        }
+        "strategy_deposit" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyDeposit(StrategyDepositEvent {
+                strategy,
+                amount: field_i128(data, "amount")?,
+                profit_share: field_i128(data, "profit_share")?,
+                total_received: since_v2(version, || field_i128(data, "total_received"))?,
+            }))
+        }
+        "strategy_report" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyReport(StrategyReportEvent {
+                strategy,
+                gain: field_i128(data, "gain")?,
+                loss: field_i128(data, "loss")?,
+                fees: field_i128(data, "fees")?,
+                debt: field_i128(data, "debt")?,
+            }))
+        }
+        "profit_share_set" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::ProfitShareSet(ProfitShareSetEvent {
+                strategy,
+                rate: field_i128(data, "rate")?,
+            }))
+        }
+        "borrow_fee_set" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::BorrowFeeSet(BorrowFeeSetEvent {
+                strategy,
+                rate: field_i128(data, "rate")?,
+            }))
+        }
+        "borrow_fee_charged" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::BorrowFeeCharged(BorrowFeeChargedEvent {
+                strategy,
+                fee: field_i128(data, "fee")?,
+            }))
+        }
+        "strategy_manager_set" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyManagerSet(StrategyManagerSetEvent {
+                strategy,
+                manager: field_optional_address(data, "manager")?,
+            }))
+        }
+        "max_deployed_rate_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::MaxDeployedRateSet(MaxDeployedRateSetEvent {
+                rate: field_i128(data, "rate")?,
+            }))
+        }
+        "withdraw_limit_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::WithdrawLimitSet(WithdrawLimitSetEvent {
+                limit: field_i128(data, "limit")?,
+                window: field_u64(data, "window")?,
+            }))
+        }
+        "max_price_change_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::MaxPriceChangeSet(MaxPriceChangeSetEvent {
+                rate: field_i128(data, "rate")?,
+            }))
+        }
+        "price_override_granted" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::PriceOverrideGranted(
+                PriceOverrideGrantedEvent { strategy },
+            ))
+        }
+        "strategy_scheduled" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyScheduled(StrategyScheduledEvent {
+                strategy,
+                activation_time: field_u64(data, "activation_time")?,
+            }))
+        }
+        "strategy_activated" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyActivated(StrategyActivatedEvent {
+                strategy,
+            }))
+        }
+        "strategy_migrated" => {
+            let [old_strategy, new_strategy] = addresses::<2>(topics)?;
+            Ok(VaultEvent::StrategyMigrated(StrategyMigratedEvent {
+                old_strategy,
+                new_strategy,
+            }))
+        }
+        "exposure_cap_set" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::ExposureCapSet(ExposureCapSetEvent {
+                strategy,
+                cap: field_optional_i128(data, "cap")?,
+            }))
+        }
+        "max_deployed_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::MaxDeployedSet(MaxDeployedSetEvent {
+                cap: field_optional_i128(data, "cap")?,
+            }))
+        }
+        "min_supply_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::MinSupplySet(MinSupplySetEvent {
+                min_supply: field_i128(data, "min_supply")?,
+            }))
+        }
+        "council_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::CouncilSet(CouncilSetEvent {
+                council: field_optional_address(data, "council")?,
+                threshold: field_i128(data, "threshold")?,
+                window: field_u64(data, "window")?,
+            }))
+        }
+        "idle_deploy_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::IdleDeploySet(IdleDeploySetEvent {
+                strategy: field_optional_address(data, "strategy")?,
+                threshold: field_i128(data, "threshold")?,
+            }))
+        }
+        "strategy_withdraw_requested" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyWithdrawRequested(
+                StrategyWithdrawRequestedEvent {
+                    strategy,
+                    amount: field_i128(data, "amount")?,
+                    expires_at: field_u64(data, "expires_at")?,
+                },
+            ))
+        }
+        "lock_tiers_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::LockTiersSet(LockTiersSetEvent {
+                tiers: field_u32(data, "tiers")?,
+            }))
+        }
+        "lock_exemption_set" => {
+            let [account] = addresses::<1>(topics)?;
+            Ok(VaultEvent::LockExemptionSet(LockExemptionSetEvent {
+                account,
+                exempt: field_bool(data, "exempt")?,
+            }))
+        }
+        "strategy_payout_set" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyPayoutSet(StrategyPayoutSetEvent {
+                strategy,
+                payout: field_optional_address(data, "payout")?,
+            }))
+        }
+        "donation" => {
+            let [from] = addresses::<1>(topics)?;
+            Ok(VaultEvent::Donation(DonationEvent {
+                from,
+                amount: field_i128(data, "amount")?,
+            }))
+        }
+        "wind_down_started" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::WindDownStarted(WindDownStartedEvent {
+                total_assets: field_i128(data, "total_assets")?,
+                deployed: field_i128(data, "deployed")?,
+            }))
+        }
+        "balance_delta_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::BalanceDeltaSet(BalanceDeltaSetEvent {
+                enabled: field_bool(data, "enabled")?,
+            }))
+        }
+        "same_ledger_guard_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::SameLedgerGuardSet(SameLedgerGuardSetEvent {
+                enabled: field_bool(data, "enabled")?,
+            }))
+        }
+        "strategy_heartbeat" => {
+            let [strategy] = addresses::<1>(topics)?;
+            Ok(VaultEvent::StrategyHeartbeat(StrategyHeartbeatEvent {
+                strategy,
+            }))
+        }
+        "report_interval_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::ReportIntervalSet(ReportIntervalSetEvent {
+                interval: field_u64(data, "interval")?,
+            }))
+        }
+        "position_migrated" => {
+            let [owner, new_vault] = addresses::<2>(topics)?;
+            Ok(VaultEvent::PositionMigrated(PositionMigratedEvent {
+                owner,
+                new_vault,
+                shares: field_i128(data, "shares")?,
+                assets: field_i128(data, "assets")?,
+                new_shares: field_i128(data, "new_shares")?,
+            }))
+        }
+        "operator_set" => {
+            let [owner, operator] = addresses::<2>(topics)?;
+            Ok(VaultEvent::OperatorSet(OperatorSetEvent {
+                owner,
+                operator,
+                approved: field_bool(data, "approved")?,
+            }))
+        }
+        "share_lock_set" => {
+            let [owner, spender] = addresses::<2>(topics)?;
+            Ok(VaultEvent::ShareLockSet(ShareLockSetEvent {
+                owner,
+                spender,
+                shares: field_i128(data, "shares")?,
+                expiration_ledger: field_u32(data, "expiration_ledger")?,
+            }))
+        }
+        "share_lock_released" => {
+            let [owner, spender] = addresses::<2>(topics)?;
+            Ok(VaultEvent::ShareLockReleased(ShareLockReleasedEvent {
+                owner,
+                spender,
+            }))
+        }
+        "deposit_root_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::DepositRootSet(DepositRootSetEvent {
+                root: field_optional_bytes32(data, "root")?,
+            }))
+        }
+        "coupon_signer_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::CouponSignerSet(CouponSignerSetEvent {
+                signer: field_optional_bytes32(data, "signer")?,
+            }))
+        }
+        "governor_set" => {
+            let [governor] = addresses::<1>(topics)?;
+            Ok(VaultEvent::GovernorSet(GovernorSetEvent { governor }))
+        }
+        "exit_fee_set" => {
+            let [] = addresses::<0>(topics)?;
+            Ok(VaultEvent::ExitFeeSet(ExitFeeSetEvent {
+                rate: field_i128(data, "rate")?,
+                duration: field_u64(data, "duration")?,
+            }))
+        }
+        "exit_fee_charged" => {
+            let [owner] = addresses::<1>(topics)?;
+            Ok(VaultEvent::ExitFeeCharged(ExitFeeChargedEvent {
+                owner,
+                fee: field_i128(data, "fee")?,
+            }))
+        }
+        "receiver_hook_set" => {
+            let [receiver] = addresses::<1>(topics)?;
+            Ok(VaultEvent::ReceiverHookSet(ReceiverHookSetEvent {
+                receiver,
+                enabled: field_bool(data, "enabled")?,
+            }))
+        }
+        "bootstrap_shares_locked" => {
+            let [depositor] = addresses::<1>(topics)?;
+            Ok(VaultEvent::BootstrapSharesLocked(
+                BootstrapSharesLockedEvent {
+                    depositor,
+                    shares: field_i128(data, "shares")?,
+                },
+            ))
+        }
+        "metadata_updated" => {
+            let [] = addresses::<0>(topics)?;
+            let metadata = field(data, "metadata")?;
+            Ok(VaultEvent::MetadataUpdated(MetadataUpdatedEvent {
+                name: field_string(metadata, "name")?,
+                description: field_string(metadata, "description")?,
+                uri: field_string(metadata, "uri")?,
+            }))
+        }
+        _ => Err(DecodeError::UnknownEvent),
+    }?;
+    Ok((version, event))
+}
+
+/// Decodes a payload field introduced in v2, returning `None` for older versions
+fn since_v2<T>(
+    version: u32,
+    decode: impl FnOnce() -> Result<T, DecodeError>,
+) -> Result<Option<T>, DecodeError> {
+    if version >= 2 {
+        decode().map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_version(tag: &[u8]) -> Result<u32, DecodeError> {
+    let digits = tag.strip_prefix(b"v").ok_or(DecodeError::InvalidTopics)?;
+    core::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or(DecodeError::InvalidTopics)
+}
+
+fn addresses<const N: usize>(topics: &[ScVal]) -> Result<[ScAddress; N], DecodeError> {
+    if topics.len() != N {
+        return Err(DecodeError::InvalidTopics);
+    }
+    let mut out = std::vec::Vec::with_capacity(N);
+    for topic in topics {
+        let ScVal::Address(address) = topic else {
+            return Err(DecodeError::InvalidTopics);
+        };
+        out.push(address.clone());
+    }
+    out.try_into().map_err(|_| DecodeError::InvalidTopics)
+}
+
+fn field<'a>(data: &'a ScVal, name: &str) -> Result<&'a ScVal, DecodeError> {
+    let ScVal::Map(Some(ScMap(entries))) = data else {
+        return Err(DecodeError::InvalidData);
+    };
+    entries
+        .iter()
+        .find(|entry| matches!(&entry.key, ScVal::Symbol(key) if key.as_slice() == name.as_bytes()))
+        .map(|entry| &entry.val)
+        .ok_or(DecodeError::InvalidData)
+}
+
+fn field_i128(data: &ScVal, name: &str) -> Result<i128, DecodeError> {
+    match field(data, name)? {
+        ScVal::I128(parts) => Ok((i128::from(parts.hi) << 64) | i128::from(parts.lo)),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_optional_i128(data: &ScVal, name: &str) -> Result<Option<i128>, DecodeError> {
+    match field(data, name)? {
+        ScVal::Void => Ok(None),
+        _ => field_i128(data, name).map(Some),
+    }
+}
+
+fn field_bool(data: &ScVal, name: &str) -> Result<bool, DecodeError> {
+    match field(data, name)? {
+        ScVal::Bool(value) => Ok(*value),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_string(data: &ScVal, name: &str) -> Result<std::string::String, DecodeError> {
+    match field(data, name)? {
+        ScVal::String(value) => Ok(value.to_utf8_string_lossy()),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_optional_address(data: &ScVal, name: &str) -> Result<Option<ScAddress>, DecodeError> {
+    match field(data, name)? {
+        ScVal::Void => Ok(None),
+        ScVal::Address(address) => Ok(Some(address.clone())),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_optional_bytes32(data: &ScVal, name: &str) -> Result<Option<[u8; 32]>, DecodeError> {
+    match field(data, name)? {
+        ScVal::Void => Ok(None),
+        ScVal::Bytes(bytes) => bytes
+            .as_slice()
+            .try_into()
+            .map(Some)
+            .map_err(|_| DecodeError::InvalidData),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_u64(data: &ScVal, name: &str) -> Result<u64, DecodeError> {
+    match field(data, name)? {
+        ScVal::U64(value) => Ok(*value),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
+
+fn field_u32(data: &ScVal, name: &str) -> Result<u32, DecodeError> {
+    match field(data, name)? {
+        ScVal::U32(value) => Ok(*value),
+        _ => Err(DecodeError::InvalidData),
+    }
+}
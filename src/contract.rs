@@ -2,46 +2,155 @@
 //!
 //! This contract implements the OpenZeppelin FungibleVault trait with a transfer
 //! lock mechanism: depositors cannot transfer their shares until lock_time seconds
-//! after their last deposit. Withdrawals and redemptions are always allowed.
+//! after their last deposit. Locked shares can still be redeemed and withdrawn,
+//! subject to the per-window withdrawal limit, the exit fee, the share supply floor
+//! and any escrowed shares. Once the vault is winding down, holders exit with
+//! `claim_wind_down`.
 
-use soroban_sdk::{contract, contractimpl, Address, Env, MuxedAddress, String, Vec};
+use soroban_sdk::{
+    contract, contractimpl, contractmeta, panic_with_error, Address, BytesN, Env, Map,
+    MuxedAddress, String, Vec,
+};
 use stellar_tokens::{
     fungible::{Base, FungibleToken},
     vault::{FungibleVault, Vault},
 };
 
-use crate::{storage, strategy::StrategyVault};
+use crate::{
+    storage::{
+        self, AccountingSnapshot, AssetInfo, CouncilConfig, DepositCoupon, ExitFee, IdleDeploy,
+        LaunchCap, LaunchConfig, LaunchSeed, LockTier, RedemptionRecord, RoundingInfo, ShareLock,
+        StrategyActivity, StrategyData, StrategyWithdrawRequest, UserPnl, VaultMetadata,
+        VaultStats, WithdrawLimit,
+    },
+    strategy::{Rate, StrategyVault, StrategyVaultError, ROUNDING},
+};
+
+/// Crate version of this build
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+contractmeta!(key = "binver", val = env!("CARGO_PKG_VERSION"));
+// Revision of the public entrypoint interface; bump on breaking changes
+contractmeta!(key = "interface", val = "2");
+contractmeta!(key = "flavor", val = "strategy-vault");
 
 #[contract]
 pub struct StrategyVaultContract;
 
+/// The constructor has its own impl so the lint allowance for its argument count also
+/// covers the argument builder `contractimpl` generates for it
+mod constructor {
+    #![allow(clippy::too_many_arguments)]
+
+    use super::*;
+
+    #[contractimpl]
+    impl StrategyVaultContract {
+        /// Initializes the vault
+        ///
+        /// # Arguments
+        /// * `admin` - Address allowed to manage vault configuration
+        /// * `name` - Name for the vault share token
+        /// * `symbol` - Symbol for the vault share token
+        /// * `asset` - Address of the underlying token contract
+        /// * `decimals_offset` - Virtual offset for inflation attack protection (0-10)
+        /// * `strategies` - List of authorized strategy contract addresses, each starting with
+        ///   a zero exposure cap
+        /// * `lock_time` - Delay in seconds before depositors can transfer their shares
+        /// * `min_initial_deposit` - Minimum assets required for the first deposit
+        /// * `strategy_delay` - Delay in seconds before a newly added strategy can be activated
+        /// * `launch` - Optional seed deposit made during initialization, so the vault never
+        ///   exists without shares, and optional deposit cap schedule for a guarded launch
+        pub fn __constructor(
+            e: Env,
+            admin: Address,
+            name: String,
+            symbol: String,
+            asset: Address,
+            decimals_offset: u32,
+            strategies: Vec<Address>,
+            lock_time: u64,
+            min_initial_deposit: i128,
+            strategy_delay: u64,
+            launch: LaunchConfig,
+        ) {
+            if min_initial_deposit < 0 {
+                panic_with_error!(&e, StrategyVaultError::InvalidConfiguration);
+            }
+            Vault::set_asset(&e, asset);
+            Vault::set_decimals_offset(&e, decimals_offset);
+            Base::set_metadata(&e, Vault::decimals(&e), name.clone(), symbol);
+
+            // Initialize custom storage
+            storage::set_admin(&e, &admin);
+            storage::set_metadata(
+                &e,
+                &VaultMetadata {
+                    name,
+                    description: String::from_str(&e, ""),
+                    uri: String::from_str(&e, ""),
+                },
+            );
+            storage::set_lock_time(&e, &lock_time);
+            storage::set_min_initial_deposit(&e, &min_initial_deposit);
+            StrategyVault::set_initial_strategies(&e, &strategies);
+            storage::set_strategy_delay(&e, &strategy_delay);
+            storage::set_max_deployed_rate(&e, Rate::MAX);
+            if let LaunchCap::Schedule(cap) = &launch.deposit_cap {
+                StrategyVault::set_deposit_cap(&e, cap);
+            }
+
+            if let LaunchSeed::Deposit(seed) = launch.seed {
+                StrategyVault::deposit_assets(
+                    &e,
+                    seed.amount,
+                    &seed.depositor,
+                    &seed.depositor,
+                    &seed.depositor,
+                );
+            }
+        }
+    }
+}
+
 #[contractimpl]
 impl StrategyVaultContract {
-    /// Initializes the vault
-    ///
-    /// # Arguments
-    /// * `name` - Name for the vault share token
-    /// * `symbol` - Symbol for the vault share token
-    /// * `asset` - Address of the underlying token contract
-    /// * `decimals_offset` - Virtual offset for inflation attack protection (0-10)
-    /// * `strategies` - List of authorized strategy contract addresses
-    /// * `lock_time` - Delay in seconds before depositors can transfer their shares
-    pub fn __constructor(
-        e: Env,
-        name: String,
-        symbol: String,
-        asset: Address,
-        decimals_offset: u32,
-        strategies: Vec<Address>,
-        lock_time: u64,
-    ) {
-        Vault::set_asset(&e, asset);
-        Vault::set_decimals_offset(&e, decimals_offset);
-        Base::set_metadata(&e, Vault::decimals(&e), name, symbol);
+    /// Returns the crate version of the deployed build
+    pub fn version(e: Env) -> String {
+        String::from_str(&e, VERSION)
+    }
+
+    /// Returns the admin address
+    pub fn admin(e: Env) -> Address {
+        storage::extend_instance(&e);
+        storage::get_admin(&e)
+    }
+
+    /// Returns the governor that controls fee parameters (the admin until one is set)
+    pub fn governor(e: Env) -> Address {
+        storage::extend_instance(&e);
+        storage::get_governor(&e)
+    }
+
+    /// Governor hands control of the fee parameters to a new governor, such as a DAO
+    /// contract
+    pub fn set_governor(e: Env, governor: Address) {
+        storage::get_governor(&e).require_auth();
+        StrategyVault::set_governor(&e, &governor);
+        storage::extend_instance(&e);
+    }
 
-        // Initialize custom storage
-        storage::set_lock_time(&e, &lock_time);
-        storage::set_strategies(&e, &strategies);
+    /// Returns the vault's display name, description and metadata URI
+    pub fn get_metadata(e: Env) -> VaultMetadata {
+        storage::extend_instance(&e);
+        storage::get_metadata(&e)
+    }
+
+    /// Admin updates the vault's display metadata
+    pub fn set_metadata(e: Env, metadata: VaultMetadata) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_metadata(&e, &metadata);
+        storage::extend_instance(&e);
     }
 
     /// Returns the lock time in seconds
@@ -50,18 +159,738 @@ impl StrategyVaultContract {
         storage::get_lock_time(&e)
     }
 
+    /// Returns the minimum amount of assets required for the first deposit
+    pub fn min_initial_deposit(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_min_initial_deposit(&e)
+    }
+
+    /// Returns the base asset: the underlying itself, or, if the underlying is another
+    /// vault's share token, that vault's base asset
+    pub fn base_asset(e: Env) -> Address {
+        storage::extend_instance(&e);
+        StrategyVault::to_base_assets(&e, 0).0
+    }
+
+    /// Converts shares of this vault into base asset units by chaining
+    /// `convert_to_assets` through every nested vault
+    pub fn convert_to_base_assets(e: Env, shares: i128) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::to_base_assets(&e, Vault::convert_to_assets(&e, shares)).1
+    }
+
+    /// Returns whether `account` skips the deposit lock and the exit fee
+    pub fn is_lock_exempt(e: Env, account: Address) -> bool {
+        storage::extend_instance(&e);
+        storage::get_lock_exempt(&e, &account)
+    }
+
+    /// Admin exempts an account, such as a market maker or the treasury, from the
+    /// deposit lock and the exit fee, or revokes the exemption
+    pub fn set_lock_exempt(e: Env, account: Address, exempt: bool) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_lock_exempt(&e, &account, exempt);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the lock time tiers by position size
+    pub fn lock_tiers(e: Env) -> Vec<LockTier> {
+        storage::extend_instance(&e);
+        storage::get_lock_tiers(&e)
+    }
+
+    /// Admin sets longer lock times for depositors whose position, valued after each
+    /// deposit, reaches a tier's `min_assets`. Tiers must be sorted by `min_assets`.
+    pub fn set_lock_tiers(e: Env, tiers: Vec<LockTier>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_lock_tiers(&e, &tiers);
+        storage::extend_instance(&e);
+    }
+
     /// Returns seconds remaining until user's shares unlock, or 0 if not locked
     pub fn lock_duration(e: Env, user: Address) -> u64 {
         storage::extend_instance(&e);
         StrategyVault::get_lock_time(&e, &user)
     }
 
+    /// Returns true while the user's shares cannot be transferred. Locked shares can
+    /// still be redeemed and withdrawn, up to `max_redeem`/`max_withdraw`.
+    pub fn is_locked(e: Env, user: Address) -> bool {
+        storage::extend_instance(&e);
+        StrategyVault::get_lock_time(&e, &user) > 0
+    }
+
     /// Strategy withdraws tokens from the vault (decreases total_assets and share price)
     pub fn strategy_withdraw(e: Env, strategy: Address, amount: i128) {
         strategy.require_auth();
         StrategyVault::withdraw(&e, &strategy, amount);
         storage::extend_instance(&e);
     }
+
+    /// Strategy signals it is alive without moving funds, which counts as a report for
+    /// the report interval
+    pub fn strategy_heartbeat(e: Env, strategy: Address) {
+        strategy.require_auth();
+        StrategyVault::heartbeat(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the seconds strategies may go without reporting before deposits pause
+    pub fn report_interval(e: Env) -> u64 {
+        storage::extend_instance(&e);
+        storage::get_report_interval(&e)
+    }
+
+    /// Admin sets how long strategies holding deployed assets may go without a
+    /// `strategy_deposit` or `strategy_heartbeat` before deposits pause; 0 disables it
+    pub fn set_report_interval(e: Env, interval: u64) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_report_interval(&e, interval);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns whether deposits are paused because strategy reports are stale
+    pub fn is_degraded(e: Env) -> bool {
+        storage::extend_instance(&e);
+        StrategyVault::is_degraded(&e)
+    }
+
+    /// Strategy asks to withdraw more than the council threshold; the council executes
+    /// the withdrawal with `approve_withdraw` before the request expires
+    pub fn request_withdraw(e: Env, strategy: Address, amount: i128) {
+        strategy.require_auth();
+        StrategyVault::request_withdraw(&e, &strategy, amount);
+        storage::extend_instance(&e);
+    }
+
+    /// Council approves and executes a strategy's pending withdrawal request
+    pub fn approve_withdraw(e: Env, strategy: Address) {
+        let Some(config) = storage::get_council(&e) else {
+            panic_with_error!(&e, StrategyVaultError::InvalidConfiguration);
+        };
+        config.council.require_auth();
+        StrategyVault::approve_withdraw(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the strategy's pending withdrawal request, if any
+    pub fn withdraw_request(e: Env, strategy: Address) -> Option<StrategyWithdrawRequest> {
+        storage::extend_instance(&e);
+        storage::get_withdraw_request(&e, &strategy)
+    }
+
+    /// Returns the council that approves large strategy withdrawals, if any
+    pub fn council(e: Env) -> Option<CouncilConfig> {
+        storage::extend_instance(&e);
+        storage::get_council(&e)
+    }
+
+    /// Returns what `strategy` can still withdraw without council approval in its current
+    /// council window
+    pub fn council_budget(e: Env, strategy: Address) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::council_budget(&e, &strategy)
+    }
+
+    /// Admin sets a council that must approve strategy withdrawals beyond `threshold`
+    /// per `window` seconds, summed per strategy, or removes it with `None`
+    pub fn set_council(e: Env, config: Option<CouncilConfig>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_council(&e, &config);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the default strategy and idle threshold used by `deploy_idle`, if set
+    pub fn idle_deploy(e: Env) -> Option<IdleDeploy> {
+        storage::extend_instance(&e);
+        storage::get_idle_deploy(&e)
+    }
+
+    /// Admin designates a default strategy for idle assets above `threshold`, or
+    /// disables `deploy_idle` with `None`
+    pub fn set_idle_deploy(e: Env, config: Option<IdleDeploy>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_idle_deploy(&e, &config);
+        storage::extend_instance(&e);
+    }
+
+    /// Anyone pushes the idle assets above the threshold to the default strategy.
+    /// Returns the assets deployed.
+    pub fn deploy_idle(e: Env) -> i128 {
+        let amount = StrategyVault::deploy_idle(&e);
+        storage::extend_instance(&e);
+        amount
+    }
+
+    /// Extends the TTL of all of a user's persistent entries, including their share balance.
+    /// Permissionless so keepers or the user can keep inactive positions alive.
+    pub fn extend_user_ttl(e: Env, user: Address) {
+        Base::balance(&e, &user);
+        storage::extend_user(&e, &user);
+        storage::extend_instance(&e);
+    }
+
+    /// Strategy returns tokens to the vault (increases total_assets and share price),
+    /// keeping its profit share of any gain
+    pub fn strategy_deposit(e: Env, strategy: Address, amount: i128) {
+        strategy.require_auth();
+        StrategyVault::deposit(&e, &strategy, &strategy, amount);
+        storage::extend_instance(&e);
+    }
+
+    /// Transfers assets into the vault without minting shares, raising the share price
+    /// for all holders. Only accepted once the vault has shares and before wind-down.
+    pub fn donate(e: Env, from: Address, amount: i128) {
+        from.require_auth();
+        StrategyVault::donate(&e, &from, amount);
+        storage::extend_instance(&e);
+    }
+
+    /// Strategy manager withdraws tokens from the vault to the strategy
+    pub fn manager_withdraw(e: Env, manager: Address, strategy: Address, amount: i128) {
+        manager.require_auth();
+        StrategyVault::require_manager(&e, &strategy, &manager);
+        StrategyVault::withdraw(&e, &strategy, amount);
+        storage::extend_instance(&e);
+    }
+
+    /// Strategy manager returns tokens from its own balance to the vault for the strategy
+    pub fn manager_deposit(e: Env, manager: Address, strategy: Address, amount: i128) {
+        manager.require_auth();
+        StrategyVault::require_manager(&e, &strategy, &manager);
+        StrategyVault::deposit(&e, &strategy, &manager, amount);
+        storage::extend_instance(&e);
+    }
+
+    /// Strategy manager sends the strategy's heartbeat, like `strategy_heartbeat`
+    pub fn manager_heartbeat(e: Env, manager: Address, strategy: Address) {
+        manager.require_auth();
+        StrategyVault::require_manager(&e, &strategy, &manager);
+        StrategyVault::heartbeat(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the registered strategies
+    pub fn strategies(e: Env) -> Vec<Address> {
+        storage::extend_instance(&e);
+        storage::get_strategies(&e)
+    }
+
+    /// Returns strategies scheduled for addition, mapped to their activation timestamp
+    pub fn pending_strategies(e: Env) -> Map<Address, u64> {
+        storage::extend_instance(&e);
+        storage::get_pending_strategies(&e)
+    }
+
+    /// Returns the delay in seconds between scheduling and activating a strategy
+    pub fn strategy_delay(e: Env) -> u64 {
+        storage::extend_instance(&e);
+        storage::get_strategy_delay(&e)
+    }
+
+    /// Admin schedules a new strategy; it can be activated after `strategy_delay`
+    pub fn add_strategy(e: Env, strategy: Address) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::schedule_strategy(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Activates a scheduled strategy once its timelock has passed (permissionless)
+    pub fn activate_strategy(e: Env, strategy: Address) {
+        StrategyVault::activate_strategy(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Admin moves a strategy's accounting (net impact, profit share, manager), pending
+    /// withdrawal request, council window, price override, last report time and
+    /// idle-deploy target to its redeployed address and deauthorizes the old address. The
+    /// new address must have been scheduled with `add_strategy` and passed the strategy
+    /// delay.
+    pub fn migrate_strategy(e: Env, old_strategy: Address, new_strategy: Address) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::migrate_strategy(&e, &old_strategy, &new_strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the assets currently deployed to strategies
+    pub fn deployed_assets(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::deployed_assets(&e)
+    }
+
+    /// Returns the maximum fraction of managed assets (idle + deployed) that strategies
+    /// may hold, in SCALAR_7
+    pub fn max_deployed_rate(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_max_deployed_rate(&e).value()
+    }
+
+    /// Admin sets the maximum fraction of managed assets that strategies may hold
+    pub fn set_max_deployed_rate(e: Env, rate: i128) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_max_deployed_rate(&e, rate);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the absolute cap on assets deployed across all strategies, if any
+    pub fn max_deployed(e: Env) -> Option<i128> {
+        storage::extend_instance(&e);
+        storage::get_max_deployed(&e)
+    }
+
+    /// Admin sets or clears the absolute cap on assets deployed across all strategies
+    pub fn set_max_deployed(e: Env, cap: Option<i128>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_max_deployed(&e, cap);
+        storage::extend_instance(&e);
+    }
+
+    /// Admin sets or clears the cap on a strategy's outstanding withdrawals
+    pub fn set_exposure_cap(e: Env, strategy: Address, cap: Option<i128>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_exposure_cap(&e, &strategy, cap);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the maximum share price change a single strategy operation may cause,
+    /// in SCALAR_7 (0 = unlimited)
+    pub fn max_price_change(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_max_price_change(&e).value()
+    }
+
+    /// Admin sets the maximum share price change per strategy operation (SCALAR_7, 0-100%,
+    /// 0 = unlimited)
+    pub fn set_max_price_change(e: Env, rate: i128) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_max_price_change(&e, rate);
+        storage::extend_instance(&e);
+    }
+
+    /// Admin allows the strategy's next operation to exceed the share price change limit
+    pub fn grant_price_override(e: Env, strategy: Address) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::grant_price_override(&e, &strategy);
+        storage::extend_instance(&e);
+    }
+
+    /// Admin sets or clears the manager allowed to act on a strategy's behalf
+    pub fn set_strategy_manager(e: Env, strategy: Address, manager: Option<Address>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_manager(&e, &strategy, manager);
+        storage::extend_instance(&e);
+    }
+
+    /// Strategy sets or clears the address its profit share is sent to when it returns
+    /// funds. Only the strategy itself can change it.
+    pub fn set_strategy_payout(e: Env, strategy: Address, payout: Option<Address>) {
+        strategy.require_auth();
+        StrategyVault::set_payout(&e, &strategy, payout);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns a strategy's accounting and configuration
+    pub fn get_strategy(e: Env, strategy: Address) -> StrategyData {
+        storage::extend_instance(&e);
+        StrategyVault::require_strategy(&e, &strategy);
+        storage::get_strategy_data(&e, &strategy)
+    }
+
+    /// Governor sets the share of returned profit a strategy keeps (SCALAR_7, 0-100%)
+    pub fn set_profit_share(e: Env, strategy: Address, rate: i128) {
+        storage::get_governor(&e).require_auth();
+        StrategyVault::set_profit_share(&e, &strategy, rate);
+        storage::extend_instance(&e);
+    }
+
+    /// Governor sets a one-time fee (SCALAR_7, below 100%) withheld from each of a
+    /// strategy's withdrawals and kept by the vault; the strategy still owes the full
+    /// amount
+    pub fn set_borrow_fee(e: Env, strategy: Address, rate: i128) {
+        storage::get_governor(&e).require_auth();
+        StrategyVault::set_borrow_fee(&e, &strategy, rate);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the per-window withdrawal limit
+    pub fn withdraw_limit(e: Env) -> WithdrawLimit {
+        storage::extend_instance(&e);
+        storage::get_withdraw_limit(&e)
+    }
+
+    /// Returns the assets that can still be withdrawn in the current window
+    pub fn withdraw_capacity(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::withdraw_capacity(&e)
+    }
+
+    /// Admin limits the assets withdrawn by depositors per `window` seconds.
+    /// A zero window disables the limit.
+    pub fn set_withdraw_limit(e: Env, limit: WithdrawLimit) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_withdraw_limit(&e, &limit);
+        storage::extend_instance(&e);
+    }
+
+    /// Deposits the entire token balance of `from`, returning the shares credited to
+    /// `receiver`. A classic account's native XLM balance includes its minimum reserve,
+    /// which cannot be transferred, so XLM accounts should `deposit` their spendable amount.
+    pub fn deposit_all(e: Env, from: Address, receiver: Address) -> i128 {
+        let shares = StrategyVault::deposit_all(&e, &from, &receiver);
+        storage::extend_instance(&e);
+        shares
+    }
+
+    /// Deposits like `deposit`, tagged with a client-supplied operation ID. Reusing an ID
+    /// for the same `from` within about 7 days fails with `DuplicateOperation`, so routers
+    /// can retry safely. Returns the shares credited to `receiver`.
+    pub fn deposit_with_id(
+        e: Env,
+        assets: i128,
+        receiver: Address,
+        from: Address,
+        operator: Address,
+        op_id: BytesN<32>,
+    ) -> i128 {
+        let (_, shares) =
+            StrategyVault::deposit_with_id(&e, assets, &receiver, &from, &operator, &op_id);
+        storage::extend_instance(&e);
+        shares
+    }
+
+    /// Returns whether `user` has used the operation ID
+    pub fn operation_used(e: Env, user: Address, op_id: BytesN<32>) -> bool {
+        storage::extend_instance(&e);
+        storage::get_operation_used(&e, &user, &op_id)
+    }
+
+    /// Deposits like `deposit`, failing with `Expired` once the ledger timestamp is past
+    /// `deadline`. Returns the shares credited to `receiver`.
+    pub fn deposit_with_deadline(
+        e: Env,
+        assets: i128,
+        receiver: Address,
+        from: Address,
+        operator: Address,
+        deadline: u64,
+    ) -> i128 {
+        StrategyVault::require_deadline(&e, deadline);
+        let (_, shares) = StrategyVault::deposit_assets(&e, assets, &receiver, &from, &operator);
+        storage::extend_instance(&e);
+        shares
+    }
+
+    /// Mints like `mint`, failing with `Expired` once the ledger timestamp is past
+    /// `deadline`. Returns the assets paid by `from`.
+    pub fn mint_with_deadline(
+        e: Env,
+        shares: i128,
+        receiver: Address,
+        from: Address,
+        operator: Address,
+        deadline: u64,
+    ) -> i128 {
+        StrategyVault::require_deadline(&e, deadline);
+        let (assets, _) = StrategyVault::mint_shares(&e, shares, &receiver, &from, &operator);
+        storage::extend_instance(&e);
+        assets
+    }
+
+    /// Redeems like `redeem`, failing with `Expired` once the ledger timestamp is past
+    /// `deadline`. Returns the assets sent to `receiver`.
+    pub fn redeem_with_deadline(
+        e: Env,
+        shares: i128,
+        receiver: Address,
+        owner: Address,
+        operator: Address,
+        deadline: u64,
+    ) -> i128 {
+        StrategyVault::require_deadline(&e, deadline);
+        let assets = StrategyVault::redeem_shares(&e, shares, &receiver, &owner, &operator);
+        storage::extend_instance(&e);
+        assets
+    }
+
+    /// Deposits for a receiver on the deposit allowlist, proven by the sibling hashes
+    /// from their leaf to the merkle root. Returns the shares credited to `receiver`.
+    pub fn deposit_with_proof(
+        e: Env,
+        assets: i128,
+        receiver: Address,
+        from: Address,
+        operator: Address,
+        proof: Vec<BytesN<32>>,
+    ) -> i128 {
+        let (_, shares) =
+            StrategyVault::deposit_with_proof(&e, assets, &receiver, &from, &operator, &proof);
+        storage::extend_instance(&e);
+        shares
+    }
+
+    /// Deposits for the receiver of a deposit coupon signed by the coupon signer.
+    /// Returns the shares credited to the coupon's receiver.
+    pub fn deposit_with_coupon(
+        e: Env,
+        assets: i128,
+        from: Address,
+        operator: Address,
+        coupon: DepositCoupon,
+        signature: BytesN<64>,
+    ) -> i128 {
+        let (_, shares) =
+            StrategyVault::deposit_with_coupon(&e, assets, &from, &operator, &coupon, &signature);
+        storage::extend_instance(&e);
+        shares
+    }
+
+    /// Returns the assets `receiver` has deposited with coupons
+    pub fn coupon_used(e: Env, receiver: Address) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_coupon_used(&e, &receiver)
+    }
+
+    /// Returns the ed25519 public key that signs deposit coupons, if coupons are enabled
+    pub fn coupon_signer(e: Env) -> Option<BytesN<32>> {
+        storage::extend_instance(&e);
+        storage::get_coupon_signer(&e)
+    }
+
+    /// Admin sets the ed25519 public key that signs deposit coupons. While a signer is
+    /// set, deposits without a coupon need an allowlist proof, so the vault is invite-only
+    /// unless a root is set too.
+    pub fn set_coupon_signer(e: Env, signer: Option<BytesN<32>>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_coupon_signer(&e, &signer);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the share supply that redemptions may not go below (0 = no floor)
+    pub fn min_supply(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_min_supply(&e)
+    }
+
+    /// Admin sets a share supply floor that redemptions and withdrawals may not go
+    /// below, keeping the share price well-conditioned. It is lifted by `wind_down`.
+    pub fn set_min_supply(e: Env, min_supply: i128) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_min_supply(&e, min_supply);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the merkle root of the deposit allowlist, if deposits are gated
+    pub fn deposit_root(e: Env) -> Option<BytesN<32>> {
+        storage::extend_instance(&e);
+        storage::get_deposit_root(&e)
+    }
+
+    /// Admin gates deposits and mints behind a merkle allowlist of receivers, or opens
+    /// them to everyone with `None`
+    pub fn set_deposit_root(e: Env, root: Option<BytesN<32>>) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_deposit_root(&e, &root);
+        storage::extend_instance(&e);
+    }
+
+    /// Redeems as many of the owner's shares as can currently be redeemed, returning the
+    /// assets sent to `receiver`
+    pub fn redeem_max(e: Env, receiver: Address, owner: Address) -> i128 {
+        let assets = StrategyVault::redeem_max(&e, &receiver, &owner);
+        storage::extend_instance(&e);
+        assets
+    }
+
+    /// Redeems `shares` of the owner's position and deposits the proceeds into another
+    /// vault of the same underlying in one transaction, returning the new vault's shares
+    pub fn migrate_to(e: Env, new_vault: Address, owner: Address, shares: i128) -> i128 {
+        let assets = StrategyVault::redeem_shares(&e, shares, &owner, &owner, &owner);
+        let new_shares = StrategyVault::migrate_position(&e, &new_vault, &owner, shares, assets);
+        storage::extend_instance(&e);
+        new_shares
+    }
+
+    /// Returns whether deposits are credited by the vault's measured balance change
+    pub fn balance_delta(e: Env) -> bool {
+        storage::extend_instance(&e);
+        storage::get_balance_delta(&e)
+    }
+
+    /// Admin enables balance-delta accounting for underlyings that take a fee on
+    /// transfer: deposits and strategy returns are credited with the assets the vault
+    /// actually received
+    pub fn set_balance_delta(e: Env, enabled: bool) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_balance_delta(&e, enabled);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns whether holders are barred from exiting in the ledger of a deposit
+    pub fn same_ledger_guard(e: Env) -> bool {
+        storage::extend_instance(&e);
+        storage::get_same_ledger_guard(&e)
+    }
+
+    /// Admin bars holders from redeeming, withdrawing or transferring shares in the
+    /// ledger of their last deposit, without a full deposit lock
+    pub fn set_same_ledger_guard(e: Env, enabled: bool) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::set_same_ledger_guard(&e, enabled);
+        storage::extend_instance(&e);
+    }
+
+    /// Admin permanently winds the vault down: deposits and strategy withdrawals are
+    /// halted, strategies are expected to return their funds, and holders exit with
+    /// `claim_wind_down`
+    pub fn wind_down(e: Env) {
+        storage::get_admin(&e).require_auth();
+        StrategyVault::wind_down(&e);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns whether the vault is winding down
+    pub fn is_wound_down(e: Env) -> bool {
+        storage::extend_instance(&e);
+        storage::get_wind_down(&e)
+    }
+
+    /// Redeems all of the owner's shares not escrowed with `approve_locked` for their
+    /// pro-rata assets once the vault is winding down, bypassing the per-window
+    /// withdrawal limit
+    pub fn claim_wind_down(e: Env, owner: Address) -> i128 {
+        let assets = StrategyVault::claim_wind_down(&e, &owner);
+        storage::extend_instance(&e);
+        assets
+    }
+
+    /// Owner approves or revokes an operator allowed to redeem and withdraw its shares
+    /// without a share allowance. Assets redeemed this way always go to the owner.
+    pub fn set_operator(e: Env, owner: Address, operator: Address, approved: bool) {
+        owner.require_auth();
+        StrategyVault::set_operator(&e, &owner, &operator, approved);
+        storage::extend_instance(&e);
+    }
+
+    /// Owner approves `spender` for `shares` and escrows them until `expiration_ledger`,
+    /// e.g. as collateral: the owner cannot redeem, withdraw or transfer them meanwhile
+    pub fn approve_locked(
+        e: Env,
+        owner: Address,
+        spender: Address,
+        shares: i128,
+        expiration_ledger: u32,
+    ) {
+        owner.require_auth();
+        StrategyVault::approve_locked(&e, &owner, &spender, shares, expiration_ledger);
+        storage::extend_instance(&e);
+    }
+
+    /// The lock's spender releases the owner's escrowed shares
+    pub fn release_lock(e: Env, owner: Address) {
+        StrategyVault::release_lock(&e, &owner);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns the owner's live share lock, if any
+    pub fn share_lock(e: Env, owner: Address) -> Option<ShareLock> {
+        storage::extend_instance(&e);
+        StrategyVault::share_lock(&e, &owner)
+    }
+
+    /// Returns the shares the owner has escrowed
+    pub fn locked_shares(e: Env, owner: Address) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::locked_shares(&e, &owner)
+    }
+
+    /// Returns whether `operator` is approved to act for `owner`
+    pub fn is_operator(e: Env, owner: Address, operator: Address) -> bool {
+        storage::extend_instance(&e);
+        storage::get_operator(&e, &owner, &operator)
+    }
+
+    /// Returns the user's cost basis and realized profit and loss
+    pub fn user_pnl(e: Env, user: Address) -> UserPnl {
+        storage::extend_instance(&e);
+        storage::get_user_pnl(&e, &user)
+    }
+
+    /// Returns supply, assets, strategy exposure and share price in one read
+    pub fn accounting_snapshot(e: Env) -> AccountingSnapshot {
+        storage::extend_instance(&e);
+        StrategyVault::accounting_snapshot(&e)
+    }
+
+    /// Returns the launch schedule's current cap on managed assets, or None if uncapped
+    pub fn deposit_cap(e: Env) -> Option<i128> {
+        storage::extend_instance(&e);
+        StrategyVault::deposit_cap(&e)
+    }
+
+    /// Returns up to the last `RECENT_ACTIVITY_LEN` strategy withdrawals and deposits,
+    /// oldest first, for UIs without an indexer
+    pub fn recent_strategy_activity(e: Env) -> Vec<StrategyActivity> {
+        storage::extend_instance(&e);
+        storage::get_recent_activity(&e)
+    }
+
+    /// Returns the rounding direction of every conversion, for auditors and SDKs
+    pub fn rounding_info(e: Env) -> RoundingInfo {
+        storage::extend_instance(&e);
+        ROUNDING
+    }
+
+    /// Returns the underlying and share token details for wallet listings in one read
+    pub fn asset_info(e: Env) -> AssetInfo {
+        storage::extend_instance(&e);
+        StrategyVault::asset_info(&e)
+    }
+
+    /// Returns the user's latest completed redemptions and withdrawals, oldest first,
+    /// for account pages without an indexer
+    pub fn redemption_history(e: Env, user: Address) -> Vec<RedemptionRecord> {
+        storage::extend_instance(&e);
+        storage::get_redemptions(&e, &user)
+    }
+
+    /// Returns lifetime deposit, withdrawal and fee counters
+    pub fn get_stats(e: Env) -> VaultStats {
+        storage::extend_instance(&e);
+        storage::get_stats(&e)
+    }
+
+    /// Returns the exit fee configuration
+    pub fn exit_fee(e: Env) -> ExitFee {
+        storage::extend_instance(&e);
+        storage::get_exit_fee(&e)
+    }
+
+    /// Returns the exit fee rate `owner` would currently pay, in SCALAR_7
+    pub fn exit_fee_rate(e: Env, owner: Address) -> i128 {
+        storage::extend_instance(&e);
+        StrategyVault::exit_fee_rate(&e, &owner).value()
+    }
+
+    /// Governor sets an exit fee that decays to zero over `duration` seconds after each
+    /// holder's last deposit. The fee stays in the vault for the remaining holders.
+    pub fn set_exit_fee(e: Env, fee: ExitFee) {
+        storage::get_governor(&e).require_auth();
+        StrategyVault::set_exit_fee(&e, &fee);
+        storage::extend_instance(&e);
+    }
+
+    /// Opts a receiver contract in or out of `on_vault_deposit`/`on_vault_redeem` hooks
+    pub fn set_receiver_hook(e: Env, receiver: Address, enabled: bool) {
+        receiver.require_auth();
+        StrategyVault::set_receiver_hook(&e, &receiver, enabled);
+        storage::extend_instance(&e);
+    }
+
+    /// Returns whether the receiver is notified of deposits and redemptions
+    pub fn receiver_hook(e: Env, receiver: Address) -> bool {
+        storage::extend_instance(&e);
+        storage::get_receiver_hook(&e, &receiver)
+    }
 }
 
 // Implement FungibleToken trait for share token functionality
@@ -69,45 +898,93 @@ impl StrategyVaultContract {
 impl FungibleToken for StrategyVaultContract {
     type ContractType = Vault;
 
-    /// Override: Depositors cannot transfer until lock expires
+    /// Override: Depositors cannot transfer until lock expires, nor move escrowed shares
     fn transfer(e: &Env, from: Address, to: MuxedAddress, amount: i128) {
         StrategyVault::require_unlocked(e, &from);
+        StrategyVault::spend_escrow(e, &from, &from, amount);
+        StrategyVault::transfer_cost_basis(e, &from, &to.address(), amount);
         Base::transfer(e, &from, &to, amount);
     }
 
-    /// Override: Depositors cannot transfer until lock expires
+    /// Override: Depositors cannot transfer until lock expires, and only the lock's
+    /// spender can move escrowed shares
     fn transfer_from(e: &Env, spender: Address, from: Address, to: Address, amount: i128) {
         StrategyVault::require_unlocked(e, &from);
+        StrategyVault::spend_escrow(e, &from, &spender, amount);
+        StrategyVault::transfer_cost_basis(e, &from, &to, amount);
         Base::transfer_from(e, &spender, &from, &to, amount);
     }
+
+    /// Override: The owner cannot cut the allowance backing a share lock
+    fn approve(e: &Env, owner: Address, spender: Address, amount: i128, live_until_ledger: u32) {
+        StrategyVault::require_lock_allowance(e, &owner, &spender, amount);
+        Base::approve(e, &owner, &spender, amount, live_until_ledger);
+    }
 }
 
 // Implement FungibleVault trait for ERC-4626 functionality
 // Override deposit/mint to track timestamps, and redeem/withdraw to check lock
 #[contractimpl(contracttrait)]
 impl FungibleVault for StrategyVaultContract {
-    /// Override: Track deposit timestamp for the receiver (who gets the shares)
+    /// Override: Track deposit timestamp for the receiver (who gets the shares).
+    /// The first deposit locks `BOOTSTRAP_SHARES` in the vault; the returned value is
+    /// the shares credited to the receiver.
     fn deposit(e: &Env, assets: i128, receiver: Address, from: Address, operator: Address) -> i128 {
-        let shares = Vault::deposit(e, assets, receiver.clone(), from, operator);
-        storage::set_last_deposit_time(e, &receiver, e.ledger().timestamp());
+        let (_, shares) = StrategyVault::deposit_assets(e, assets, &receiver, &from, &operator);
         storage::extend_instance(e);
         shares
     }
 
-    /// Override: Track mint timestamp for the receiver (who gets the shares)
+    /// Override: Track mint timestamp for the receiver (who gets the shares).
+    /// The first mint locks `BOOTSTRAP_SHARES` of the minted shares in the vault.
     fn mint(e: &Env, shares: i128, receiver: Address, from: Address, operator: Address) -> i128 {
-        let assets = Vault::mint(e, shares, receiver.clone(), from, operator);
-        storage::set_last_deposit_time(e, &receiver, e.ledger().timestamp());
+        let (assets, _) = StrategyVault::mint_shares(e, shares, &receiver, &from, &operator);
         storage::extend_instance(e);
         assets
     }
 
+    /// Override: Nothing can be deposited once the vault is winding down, and deposits
+    /// are limited by the launch schedule's cap
+    fn max_deposit(e: &Env, receiver: Address) -> i128 {
+        if storage::get_wind_down(e) || StrategyVault::is_degraded(e) {
+            return 0;
+        }
+        Vault::max_deposit(e, receiver).min(StrategyVault::deposit_room(e))
+    }
+
+    /// Override: Nothing can be minted once the vault is winding down, and mints are
+    /// limited by the launch schedule's cap
+    fn max_mint(e: &Env, receiver: Address) -> i128 {
+        if storage::get_wind_down(e) || StrategyVault::is_degraded(e) {
+            return 0;
+        }
+        let room = StrategyVault::deposit_room(e);
+        if room == i128::MAX {
+            return Vault::max_mint(e, receiver);
+        }
+        Vault::convert_to_shares(e, room)
+    }
+
+    /// Override: Net of the owner's exit fee and limited by the withdrawal capacity left
+    /// in the current window
+    fn max_withdraw(e: &Env, owner: Address) -> i128 {
+        StrategyVault::max_withdraw(e, &owner)
+    }
+
+    /// Override: Limited by the withdrawal capacity left in the current window
+    fn max_redeem(e: &Env, owner: Address) -> i128 {
+        StrategyVault::max_redeem(e, &owner)
+    }
+
+    /// Override: Charges the exit fee and counts against the per-window withdrawal limit
     fn redeem(e: &Env, shares: i128, receiver: Address, owner: Address, operator: Address) -> i128 {
-        let assets = Vault::redeem(e, shares, receiver, owner, operator);
+        let assets = StrategyVault::redeem_shares(e, shares, &receiver, &owner, &operator);
         storage::extend_instance(e);
         assets
     }
 
+    /// Override: Burns extra shares for the exit fee and counts against the per-window
+    /// withdrawal limit
     fn withdraw(
         e: &Env,
         assets: i128,
@@ -115,9 +992,8 @@ impl FungibleVault for StrategyVaultContract {
         owner: Address,
         operator: Address,
     ) -> i128 {
-        let shares = Vault::withdraw(e, assets, receiver, owner, operator);
+        let shares = StrategyVault::withdraw_assets(e, assets, &receiver, &owner, &operator);
         storage::extend_instance(e);
         shares
     }
-
 }
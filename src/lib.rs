@@ -1,6 +1,9 @@
 #![no_std]
 
+#[cfg(any(test, feature = "std"))]
+pub mod client;
 mod contract;
+mod merkle;
 mod storage;
 mod strategy;
 pub use contract::{StrategyVaultContract, StrategyVaultContractClient};
@@ -0,0 +1,27 @@
+//! Merkle allowlist verification
+//!
+//! Trees hash each pair of nodes in sorted order, so proofs carry only the sibling
+//! hashes and no left/right flags. Leaves are the SHA-256 of the address's XDR.
+
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// Returns the leaf committing to `address`
+pub fn leaf(e: &Env, address: &Address) -> BytesN<32> {
+    e.crypto().sha256(&address.clone().to_xdr(e)).to_bytes()
+}
+
+/// Returns the parent of two nodes, independent of their order
+pub fn hash_pair(e: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = Bytes::from(first);
+    data.append(&Bytes::from(second));
+    e.crypto().sha256(&data).to_bytes()
+}
+
+/// Returns true if `proof` leads from `leaf` to `root`
+pub fn verify(e: &Env, root: &BytesN<32>, leaf: BytesN<32>, proof: &Vec<BytesN<32>>) -> bool {
+    let node = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(e, &node, &sibling));
+    &node == root
+}
@@ -1,14 +1,351 @@
-use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Vec as SorobanVec};
+use crate::strategy::Rate;
+use soroban_sdk::{
+    contracttype, unwrap::UnwrapOptimized, Address, BytesN, Env, Map, String, Vec as SorobanVec,
+};
+
 use stellar_tokens::fungible::{
     BALANCE_EXTEND_AMOUNT, BALANCE_TTL_THRESHOLD, INSTANCE_EXTEND_AMOUNT, INSTANCE_TTL_THRESHOLD,
 };
 
+/// Number of strategy flows kept in the recent activity buffer
+pub const RECENT_ACTIVITY_LEN: u32 = 20;
+/// Number of redemptions kept in each holder's history
+pub const REDEMPTION_HISTORY_LEN: u32 = 10;
+/// Ledgers a used operation ID is remembered (about 7 days)
+const OPERATION_TTL: u32 = 7 * 17_280;
+/// Ledgers the recent activity buffer lives after its last write (about 7 days)
+const ACTIVITY_TTL: u32 = 7 * 17_280;
+
+/// Human-readable vault information for front-ends
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VaultMetadata {
+    pub name: String,
+    pub description: String,
+    pub uri: String,
+}
+
+/// Accounting and configuration for a single strategy
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct StrategyData {
+    /// Assets returned to the vault minus assets withdrawn by the strategy
+    /// (`total_received - total_sent`)
+    pub net_impact: i128,
+    /// Cumulative assets withdrawn by the strategy
+    pub total_sent: i128,
+    /// Cumulative assets returned to the vault, net of the strategy's profit share
+    pub total_received: i128,
+    /// Share of returned profit kept by the strategy
+    pub profit_share: Rate,
+    /// Address allowed to withdraw and deposit on the strategy's behalf
+    pub manager: Option<Address>,
+    /// Maximum outstanding withdrawals (negative net impact), unlimited if None
+    pub exposure_cap: Option<i128>,
+    /// Address the strategy's profit share is sent to; kept by the depositor if None
+    pub payout: Option<Address>,
+    /// One-time fee on each withdrawal, kept by the vault
+    pub borrow_fee: Rate,
+}
+
+/// Direction of a strategy flow
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum StrategyAction {
+    Withdraw,
+    Deposit,
+}
+
+/// A strategy flow kept for UIs without an indexer
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StrategyActivity {
+    pub strategy: Address,
+    pub action: StrategyAction,
+    pub amount: i128,
+    /// The strategy's net impact after the flow
+    pub net_impact: i128,
+    pub timestamp: u64,
+}
+
+/// A completed redemption kept in the holder's history
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RedemptionRecord {
+    pub shares: i128,
+    /// Assets paid out, net of the exit fee
+    pub assets: i128,
+    pub exit_fee: i128,
+    pub timestamp: u64,
+}
+
+/// Direction a conversion rounds in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Rounding {
+    Floor,
+    Ceil,
+}
+
+/// Rounding direction of every conversion the vault performs
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RoundingInfo {
+    /// Shares minted for deposited assets
+    pub deposit: Rounding,
+    /// Assets charged for minted shares
+    pub mint: Rounding,
+    /// Assets paid for redeemed shares
+    pub redeem: Rounding,
+    /// Shares burned for withdrawn assets
+    pub withdraw: Rounding,
+    /// Exit fee on a redemption's gross assets
+    pub exit_fee: Rounding,
+    /// Gross assets a withdrawal burns shares for, so the exit fee leaves the requested amount
+    pub withdraw_gross_up: Rounding,
+    /// Profit share kept by a strategy
+    pub profit_share: Rounding,
+    /// Borrow fee withheld from a strategy withdrawal
+    pub borrow_fee: Rounding,
+}
+
+/// Cap on assets withdrawn from the vault per window; a zero window disables it
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct WithdrawLimit {
+    pub limit: i128,
+    pub window: u64,
+}
+
+/// Fee on redemptions that decays linearly from `rate` (SCALAR_7) to zero over
+/// `duration` seconds after a holder's last deposit; a zero rate disables it
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct ExitFee {
+    pub rate: Rate,
+    pub duration: u64,
+}
+
+/// A holder's cost basis and realized profit and loss, in assets. The weighted-average
+/// deposit price is `cost_basis` divided by the holder's share balance.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct UserPnl {
+    /// Assets paid for the shares the holder still has
+    pub cost_basis: i128,
+    /// Assets received on exits minus the cost basis of the shares burned
+    pub realized: i128,
+}
+
+/// An invitation to deposit, signed off-chain by the coupon signer
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositCoupon {
+    pub receiver: Address,
+    /// Total assets the receiver may deposit with coupons
+    pub max_amount: i128,
+    /// Ledger timestamp after which the coupon is no longer accepted
+    pub expiry: u64,
+}
+
+/// Council that must approve strategy withdrawals above `threshold` per `window`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CouncilConfig {
+    pub council: Address,
+    /// Assets each strategy may withdraw per window without approval
+    pub threshold: i128,
+    /// Seconds the council has to approve a request, and the length of the window
+    /// strategy withdrawals are summed over
+    pub window: u64,
+}
+
+/// Default strategy that idle assets above `threshold` are pushed to by `deploy_idle`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct IdleDeploy {
+    pub strategy: Address,
+    /// Assets kept idle in the vault for redemptions
+    pub threshold: i128,
+}
+
+/// A strategy withdrawal waiting for council approval
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StrategyWithdrawRequest {
+    pub amount: i128,
+    pub expires_at: u64,
+}
+
+/// Lock time applied to depositors whose position is worth at least `min_assets`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LockTier {
+    pub min_assets: i128,
+    pub lock_time: u64,
+}
+
+/// The vault's accounting state at one point in time, for audits and monitoring
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AccountingSnapshot {
+    pub total_supply: i128,
+    /// Underlying balance held by the vault (`total_assets`)
+    pub total_assets: i128,
+    /// Assets outstanding at strategies (the sum of negative net impacts)
+    pub deployed: i128,
+    /// Sum of every strategy's net impact
+    pub net_impact: i128,
+    /// Assets one whole share converts to
+    pub share_price: i128,
+    pub timestamp: u64,
+}
+
+/// Shares escrowed for a spender with `approve_locked`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ShareLock {
+    pub spender: Address,
+    pub shares: i128,
+    /// Last ledger the lock and the allowance backing it are live
+    pub expiration_ledger: u32,
+}
+
+/// The underlying and share token details a wallet needs to list the vault
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AssetInfo {
+    pub asset: Address,
+    pub asset_decimals: u32,
+    /// The vault itself, which is the share token
+    pub share_token: Address,
+    pub share_name: String,
+    pub share_symbol: String,
+    pub share_decimals: u32,
+    /// Assets one whole share converts to
+    pub share_price: i128,
+}
+
+/// Guarded launch schedule: managed assets (idle plus deployed) are capped at
+/// `initial`, and the cap grows by `step` every `period` seconds after `start`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositCap {
+    pub initial: i128,
+    pub step: i128,
+    /// Seconds between increases; 0 keeps the cap at `initial`
+    pub period: u64,
+    pub start: u64,
+}
+
+/// First deposit made by the constructor, so the vault never exists without shares
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SeedDeposit {
+    pub depositor: Address,
+    pub amount: i128,
+}
+
+// soroban-sdk cannot convert an `Option` of a custom type held in a contracttype to
+// XDR under testutils, so the optional parts of a launch are enums
+
+/// Optional seed deposit of a launch
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LaunchSeed {
+    None,
+    Deposit(SeedDeposit),
+}
+
+/// Optional deposit cap schedule of a launch
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LaunchCap {
+    None,
+    Schedule(DepositCap),
+}
+
+/// Launch settings passed to the constructor
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LaunchConfig {
+    pub seed: LaunchSeed,
+    /// Guarded launch schedule; cannot be changed after deployment
+    pub deposit_cap: LaunchCap,
+}
+
+/// Lifetime counters for dashboards
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct VaultStats {
+    /// Assets deposited by holders
+    pub total_deposited: i128,
+    /// Assets paid out to holders, net of exit fees
+    pub total_withdrawn: i128,
+    /// Exit fees kept by the vault
+    pub exit_fees: i128,
+    /// Profit shares kept by strategies
+    pub strategy_fees: i128,
+    /// Borrow fees withheld from strategy withdrawals
+    pub borrow_fees: i128,
+    /// Addresses that have received shares from a deposit
+    pub depositors: u32,
+}
+
+/// Assets withdrawn in the window starting at `start`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct WithdrawWindow {
+    pub start: u64,
+    pub used: i128,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum StrategyStorageKey {
+    Admin,
+    Governor,
+    Metadata,
     LockTime,
+    MinInitialDeposit,
     Strategies,
+    StrategyDelay,
+    PendingStrategies,
+    MaxDeployedRate,
+    MaxDeployed,
+    WithdrawLimit,
+    WithdrawWindow,
+    ExitFee,
+    Stats,
+    MaxPriceChange,
+    WindDown,
+    BalanceDelta,
+    SameLedgerGuard,
+    RecentActivity,
+    IdleDeploy,
+    PriceOverride(Address),
+    StrategyData(Address),
     LastDepositTime(Address),
+    FeeStartTime(Address),
+    ReceiverHook(Address),
+    Operator(Address, Address),
+    Pnl(Address),
+    DepositRoot,
+    CouponSigner,
+    CouponUsed(Address),
+    MinSupply,
+    Council,
+    WithdrawRequest(Address),
+    CouncilWindow(Address),
+    LockTiers,
+    UserLockTime(Address),
+    LockExempt(Address),
+    ShareLock(Address),
+    DepositCap,
+    Redemptions(Address),
+    Operation(Address, BytesN<32>),
+    ReportInterval,
+    LastReport(Address),
 }
 
 pub fn extend_instance(e: &Env) {
@@ -17,6 +354,107 @@ pub fn extend_instance(e: &Env) {
         .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_EXTEND_AMOUNT);
 }
 
+/// Extends every persistent entry this contract keeps for `user`, skipping missing ones
+pub fn extend_user(e: &Env, user: &Address) {
+    for key in [
+        StrategyStorageKey::LastDepositTime(user.clone()),
+        StrategyStorageKey::FeeStartTime(user.clone()),
+        StrategyStorageKey::ReceiverHook(user.clone()),
+        StrategyStorageKey::Pnl(user.clone()),
+        StrategyStorageKey::CouponUsed(user.clone()),
+        StrategyStorageKey::UserLockTime(user.clone()),
+        StrategyStorageKey::LockExempt(user.clone()),
+        StrategyStorageKey::ShareLock(user.clone()),
+        StrategyStorageKey::Redemptions(user.clone()),
+    ] {
+        if e.storage().persistent().has(&key) {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+        }
+    }
+}
+
+pub fn get_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, Address>(&StrategyStorageKey::Admin)
+        .unwrap_optimized()
+}
+
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, Address>(&StrategyStorageKey::Admin, admin);
+}
+
+/// Returns the governor, which defaults to the admin until one is set
+pub fn get_governor(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, Address>(&StrategyStorageKey::Governor)
+        .unwrap_or_else(|| get_admin(e))
+}
+
+pub fn set_governor(e: &Env, governor: &Address) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, Address>(&StrategyStorageKey::Governor, governor);
+}
+
+/// Returns the merkle root of the deposit allowlist, if deposits are gated
+pub fn get_deposit_root(e: &Env) -> Option<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, BytesN<32>>(&StrategyStorageKey::DepositRoot)
+}
+
+pub fn set_deposit_root(e: &Env, root: &Option<BytesN<32>>) {
+    match root {
+        Some(root) => e
+            .storage()
+            .instance()
+            .set::<StrategyStorageKey, BytesN<32>>(&StrategyStorageKey::DepositRoot, root),
+        None => e
+            .storage()
+            .instance()
+            .remove(&StrategyStorageKey::DepositRoot),
+    }
+}
+
+/// Returns the ed25519 public key that signs deposit coupons, if coupons are enabled
+pub fn get_coupon_signer(e: &Env) -> Option<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, BytesN<32>>(&StrategyStorageKey::CouponSigner)
+}
+
+pub fn set_coupon_signer(e: &Env, signer: &Option<BytesN<32>>) {
+    match signer {
+        Some(signer) => e
+            .storage()
+            .instance()
+            .set::<StrategyStorageKey, BytesN<32>>(&StrategyStorageKey::CouponSigner, signer),
+        None => e
+            .storage()
+            .instance()
+            .remove(&StrategyStorageKey::CouponSigner),
+    }
+}
+
+pub fn get_metadata(e: &Env) -> VaultMetadata {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, VaultMetadata>(&StrategyStorageKey::Metadata)
+        .unwrap_optimized()
+}
+
+pub fn set_metadata(e: &Env, metadata: &VaultMetadata) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, VaultMetadata>(&StrategyStorageKey::Metadata, metadata);
+}
+
 pub fn get_lock_time(e: &Env) -> u64 {
     e.storage()
         .instance()
@@ -30,6 +468,63 @@ pub fn set_lock_time(e: &Env, lock_time: &u64) {
         .set::<StrategyStorageKey, u64>(&StrategyStorageKey::LockTime, lock_time);
 }
 
+pub fn get_lock_tiers(e: &Env) -> SorobanVec<LockTier> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, SorobanVec<LockTier>>(&StrategyStorageKey::LockTiers)
+        .unwrap_or_else(|| SorobanVec::new(e))
+}
+
+pub fn set_lock_tiers(e: &Env, tiers: &SorobanVec<LockTier>) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, SorobanVec<LockTier>>(&StrategyStorageKey::LockTiers, tiers);
+}
+
+/// Returns the lock time of the user's tier, or the vault's lock time if no tier applied
+pub fn get_user_lock_time(e: &Env, user: &Address) -> u64 {
+    let key = StrategyStorageKey::UserLockTime(user.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, u64>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or_else(|| get_lock_time(e))
+}
+
+/// Stores the user's tier lock time; `None` falls back to the vault's lock time
+pub fn set_user_lock_time(e: &Env, user: &Address, lock_time: Option<u64>) {
+    let key = StrategyStorageKey::UserLockTime(user.clone());
+    let Some(lock_time) = lock_time else {
+        e.storage().persistent().remove(&key);
+        return;
+    };
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, u64>(&key, &lock_time);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+pub fn get_min_initial_deposit(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, i128>(&StrategyStorageKey::MinInitialDeposit)
+        .unwrap_optimized()
+}
+
+pub fn set_min_initial_deposit(e: &Env, min_initial_deposit: &i128) {
+    e.storage().instance().set::<StrategyStorageKey, i128>(
+        &StrategyStorageKey::MinInitialDeposit,
+        min_initial_deposit,
+    );
+}
+
 pub fn get_strategies(e: &Env) -> SorobanVec<Address> {
     e.storage()
         .instance()
@@ -43,6 +538,384 @@ pub fn set_strategies(e: &Env, strategies: &SorobanVec<Address>) {
         .set::<StrategyStorageKey, SorobanVec<Address>>(&StrategyStorageKey::Strategies, strategies);
 }
 
+pub fn get_strategy_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, u64>(&StrategyStorageKey::StrategyDelay)
+        .unwrap_optimized()
+}
+
+pub fn set_strategy_delay(e: &Env, delay: &u64) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, u64>(&StrategyStorageKey::StrategyDelay, delay);
+}
+
+/// Strategies scheduled for addition, mapped to their activation timestamp
+pub fn get_pending_strategies(e: &Env) -> Map<Address, u64> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, Map<Address, u64>>(&StrategyStorageKey::PendingStrategies)
+        .unwrap_or_else(|| Map::new(e))
+}
+
+pub fn set_pending_strategies(e: &Env, pending: &Map<Address, u64>) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, Map<Address, u64>>(
+            &StrategyStorageKey::PendingStrategies,
+            pending,
+        );
+}
+
+pub fn get_max_deployed_rate(e: &Env) -> Rate {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, Rate>(&StrategyStorageKey::MaxDeployedRate)
+        .unwrap_optimized()
+}
+
+pub fn set_max_deployed_rate(e: &Env, rate: Rate) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, Rate>(&StrategyStorageKey::MaxDeployedRate, &rate);
+}
+
+pub fn get_max_deployed(e: &Env) -> Option<i128> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, i128>(&StrategyStorageKey::MaxDeployed)
+}
+
+pub fn set_max_deployed(e: &Env, cap: &Option<i128>) {
+    match cap {
+        Some(cap) => e
+            .storage()
+            .instance()
+            .set::<StrategyStorageKey, i128>(&StrategyStorageKey::MaxDeployed, cap),
+        None => e
+            .storage()
+            .instance()
+            .remove(&StrategyStorageKey::MaxDeployed),
+    }
+}
+
+pub fn get_withdraw_limit(e: &Env) -> WithdrawLimit {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, WithdrawLimit>(&StrategyStorageKey::WithdrawLimit)
+        .unwrap_or_default()
+}
+
+pub fn set_withdraw_limit(e: &Env, limit: &WithdrawLimit) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, WithdrawLimit>(&StrategyStorageKey::WithdrawLimit, limit);
+}
+
+pub fn get_withdraw_window(e: &Env) -> WithdrawWindow {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, WithdrawWindow>(&StrategyStorageKey::WithdrawWindow)
+        .unwrap_or_default()
+}
+
+pub fn set_withdraw_window(e: &Env, window: &WithdrawWindow) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, WithdrawWindow>(&StrategyStorageKey::WithdrawWindow, window);
+}
+
+pub fn get_exit_fee(e: &Env) -> ExitFee {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, ExitFee>(&StrategyStorageKey::ExitFee)
+        .unwrap_or_default()
+}
+
+pub fn set_exit_fee(e: &Env, fee: &ExitFee) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, ExitFee>(&StrategyStorageKey::ExitFee, fee);
+}
+
+pub fn get_stats(e: &Env) -> VaultStats {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, VaultStats>(&StrategyStorageKey::Stats)
+        .unwrap_or_default()
+}
+
+pub fn set_stats(e: &Env, stats: &VaultStats) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, VaultStats>(&StrategyStorageKey::Stats, stats);
+}
+
+pub fn get_max_price_change(e: &Env) -> Rate {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, Rate>(&StrategyStorageKey::MaxPriceChange)
+        .unwrap_or_default()
+}
+
+pub fn set_max_price_change(e: &Env, rate: &Rate) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, Rate>(&StrategyStorageKey::MaxPriceChange, rate);
+}
+
+pub fn get_min_supply(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, i128>(&StrategyStorageKey::MinSupply)
+        .unwrap_or(0)
+}
+
+pub fn set_min_supply(e: &Env, min_supply: i128) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, i128>(&StrategyStorageKey::MinSupply, &min_supply);
+}
+
+pub fn get_deposit_cap(e: &Env) -> Option<DepositCap> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, DepositCap>(&StrategyStorageKey::DepositCap)
+}
+
+pub fn set_deposit_cap(e: &Env, cap: &DepositCap) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, DepositCap>(&StrategyStorageKey::DepositCap, cap);
+}
+
+pub fn get_wind_down(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, bool>(&StrategyStorageKey::WindDown)
+        .unwrap_or(false)
+}
+
+pub fn set_wind_down(e: &Env) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, bool>(&StrategyStorageKey::WindDown, &true);
+}
+
+pub fn get_balance_delta(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, bool>(&StrategyStorageKey::BalanceDelta)
+        .unwrap_or(false)
+}
+
+pub fn set_balance_delta(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, bool>(&StrategyStorageKey::BalanceDelta, &enabled);
+}
+
+/// Returns the most recent strategy flows, oldest first. The buffer lives in temporary
+/// storage and is empty once it expires.
+pub fn get_recent_activity(e: &Env) -> SorobanVec<StrategyActivity> {
+    e.storage()
+        .temporary()
+        .get::<StrategyStorageKey, SorobanVec<StrategyActivity>>(
+            &StrategyStorageKey::RecentActivity,
+        )
+        .unwrap_or_else(|| SorobanVec::new(e))
+}
+
+/// Appends a strategy flow, dropping the oldest once `RECENT_ACTIVITY_LEN` are kept
+pub fn push_recent_activity(e: &Env, activity: StrategyActivity) {
+    let mut recent = get_recent_activity(e);
+    if recent.len() >= RECENT_ACTIVITY_LEN {
+        recent.pop_front();
+    }
+    recent.push_back(activity);
+    let key = StrategyStorageKey::RecentActivity;
+    e.storage()
+        .temporary()
+        .set::<StrategyStorageKey, SorobanVec<StrategyActivity>>(&key, &recent);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, ACTIVITY_TTL, ACTIVITY_TTL);
+}
+
+/// Returns the seconds strategies may go without reporting before deposits pause; 0
+/// disables the check
+pub fn get_report_interval(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, u64>(&StrategyStorageKey::ReportInterval)
+        .unwrap_or(0)
+}
+
+pub fn set_report_interval(e: &Env, interval: u64) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, u64>(&StrategyStorageKey::ReportInterval, &interval);
+}
+
+/// Returns the time of the strategy's last report or heartbeat
+pub fn get_last_report(e: &Env, strategy: &Address) -> u64 {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, u64>(&StrategyStorageKey::LastReport(strategy.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_last_report(e: &Env, strategy: &Address, timestamp: u64) {
+    e.storage().instance().set::<StrategyStorageKey, u64>(
+        &StrategyStorageKey::LastReport(strategy.clone()),
+        &timestamp,
+    );
+}
+
+pub fn remove_last_report(e: &Env, strategy: &Address) {
+    e.storage()
+        .instance()
+        .remove(&StrategyStorageKey::LastReport(strategy.clone()));
+}
+
+pub fn get_same_ledger_guard(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, bool>(&StrategyStorageKey::SameLedgerGuard)
+        .unwrap_or(false)
+}
+
+pub fn set_same_ledger_guard(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, bool>(&StrategyStorageKey::SameLedgerGuard, &enabled);
+}
+
+/// Consumes the one-shot price deviation override for a strategy, returning whether it was set
+pub fn take_price_override(e: &Env, strategy: &Address) -> bool {
+    let key = StrategyStorageKey::PriceOverride(strategy.clone());
+    let result = e.storage().instance().has(&key);
+    if result {
+        e.storage().instance().remove(&key);
+    }
+    result
+}
+
+pub fn set_price_override(e: &Env, strategy: &Address) {
+    e.storage().instance().set::<StrategyStorageKey, bool>(
+        &StrategyStorageKey::PriceOverride(strategy.clone()),
+        &true,
+    );
+}
+
+pub fn get_council(e: &Env) -> Option<CouncilConfig> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, CouncilConfig>(&StrategyStorageKey::Council)
+}
+
+pub fn set_council(e: &Env, council: &Option<CouncilConfig>) {
+    match council {
+        Some(council) => e
+            .storage()
+            .instance()
+            .set::<StrategyStorageKey, CouncilConfig>(&StrategyStorageKey::Council, council),
+        None => e.storage().instance().remove(&StrategyStorageKey::Council),
+    }
+}
+
+pub fn get_idle_deploy(e: &Env) -> Option<IdleDeploy> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, IdleDeploy>(&StrategyStorageKey::IdleDeploy)
+}
+
+pub fn set_idle_deploy(e: &Env, config: &Option<IdleDeploy>) {
+    match config {
+        Some(config) => e
+            .storage()
+            .instance()
+            .set::<StrategyStorageKey, IdleDeploy>(&StrategyStorageKey::IdleDeploy, config),
+        None => e
+            .storage()
+            .instance()
+            .remove(&StrategyStorageKey::IdleDeploy),
+    }
+}
+
+pub fn get_withdraw_request(e: &Env, strategy: &Address) -> Option<StrategyWithdrawRequest> {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, StrategyWithdrawRequest>(&StrategyStorageKey::WithdrawRequest(
+            strategy.clone(),
+        ))
+}
+
+pub fn set_withdraw_request(e: &Env, strategy: &Address, request: &StrategyWithdrawRequest) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, StrategyWithdrawRequest>(
+            &StrategyStorageKey::WithdrawRequest(strategy.clone()),
+            request,
+        );
+}
+
+pub fn remove_withdraw_request(e: &Env, strategy: &Address) {
+    e.storage()
+        .instance()
+        .remove(&StrategyStorageKey::WithdrawRequest(strategy.clone()));
+}
+
+/// Returns the strategy's unapproved withdrawals in its current council window
+pub fn get_council_window(e: &Env, strategy: &Address) -> WithdrawWindow {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, WithdrawWindow>(&StrategyStorageKey::CouncilWindow(
+            strategy.clone(),
+        ))
+        .unwrap_or_default()
+}
+
+pub fn set_council_window(e: &Env, strategy: &Address, window: &WithdrawWindow) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, WithdrawWindow>(
+            &StrategyStorageKey::CouncilWindow(strategy.clone()),
+            window,
+        );
+}
+
+pub fn remove_council_window(e: &Env, strategy: &Address) {
+    e.storage()
+        .instance()
+        .remove(&StrategyStorageKey::CouncilWindow(strategy.clone()));
+}
+
+pub fn get_strategy_data(e: &Env, strategy: &Address) -> StrategyData {
+    e.storage()
+        .instance()
+        .get::<StrategyStorageKey, StrategyData>(&StrategyStorageKey::StrategyData(
+            strategy.clone(),
+        ))
+        .unwrap_or_default()
+}
+
+pub fn set_strategy_data(e: &Env, strategy: &Address, data: &StrategyData) {
+    e.storage()
+        .instance()
+        .set::<StrategyStorageKey, StrategyData>(
+            &StrategyStorageKey::StrategyData(strategy.clone()),
+            data,
+        );
+}
+
+pub fn remove_strategy_data(e: &Env, strategy: &Address) {
+    e.storage()
+        .instance()
+        .remove(&StrategyStorageKey::StrategyData(strategy.clone()));
+}
+
 pub fn get_last_deposit_time(e: &Env, user: &Address) -> Option<u64> {
     let key = StrategyStorageKey::LastDepositTime(user.clone());
     let result = e.storage().persistent().get::<StrategyStorageKey, u64>(&key);
@@ -63,3 +936,242 @@ pub fn set_last_deposit_time(e: &Env, user: &Address, timestamp: u64) {
         .persistent()
         .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
 }
+
+/// Returns the time the user's exit fee started decaying, if they ever received shares
+/// from a deposit
+pub fn get_fee_start_time(e: &Env, user: &Address) -> Option<u64> {
+    let key = StrategyStorageKey::FeeStartTime(user.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, u64>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result
+}
+
+pub fn set_fee_start_time(e: &Env, user: &Address, timestamp: u64) {
+    let key = StrategyStorageKey::FeeStartTime(user.clone());
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, u64>(&key, &timestamp);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+pub fn get_receiver_hook(e: &Env, receiver: &Address) -> bool {
+    let key = StrategyStorageKey::ReceiverHook(receiver.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, bool>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or(false)
+}
+
+pub fn set_receiver_hook(e: &Env, receiver: &Address, enabled: bool) {
+    let key = StrategyStorageKey::ReceiverHook(receiver.clone());
+    if !enabled {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, bool>(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+/// Returns whether `operator` may redeem and withdraw for `owner` to the owner
+pub fn get_operator(e: &Env, owner: &Address, operator: &Address) -> bool {
+    let key = StrategyStorageKey::Operator(owner.clone(), operator.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, bool>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or(false)
+}
+
+pub fn set_operator(e: &Env, owner: &Address, operator: &Address, approved: bool) {
+    let key = StrategyStorageKey::Operator(owner.clone(), operator.clone());
+    if !approved {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, bool>(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+pub fn get_user_pnl(e: &Env, user: &Address) -> UserPnl {
+    let key = StrategyStorageKey::Pnl(user.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, UserPnl>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or_default()
+}
+
+pub fn set_user_pnl(e: &Env, user: &Address, pnl: &UserPnl) {
+    let key = StrategyStorageKey::Pnl(user.clone());
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, UserPnl>(&key, pnl);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+/// Returns the assets `receiver` has deposited with coupons
+pub fn get_coupon_used(e: &Env, receiver: &Address) -> i128 {
+    let key = StrategyStorageKey::CouponUsed(receiver.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, i128>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or(0)
+}
+
+pub fn set_coupon_used(e: &Env, receiver: &Address, used: i128) {
+    let key = StrategyStorageKey::CouponUsed(receiver.clone());
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, i128>(&key, &used);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+/// Returns whether `account` skips the deposit lock and the exit fee
+pub fn get_lock_exempt(e: &Env, account: &Address) -> bool {
+    let key = StrategyStorageKey::LockExempt(account.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, bool>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or(false)
+}
+
+pub fn set_lock_exempt(e: &Env, account: &Address, exempt: bool) {
+    let key = StrategyStorageKey::LockExempt(account.clone());
+    if !exempt {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, bool>(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+/// Returns the user's latest redemptions, oldest first
+pub fn get_redemptions(e: &Env, user: &Address) -> SorobanVec<RedemptionRecord> {
+    let key = StrategyStorageKey::Redemptions(user.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, SorobanVec<RedemptionRecord>>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result.unwrap_or_else(|| SorobanVec::new(e))
+}
+
+/// Appends a redemption, dropping the oldest once `REDEMPTION_HISTORY_LEN` are kept
+pub fn push_redemption(e: &Env, user: &Address, record: RedemptionRecord) {
+    let mut history = get_redemptions(e, user);
+    if history.len() >= REDEMPTION_HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(record);
+    let key = StrategyStorageKey::Redemptions(user.clone());
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, SorobanVec<RedemptionRecord>>(&key, &history);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
+
+/// Returns whether `user` has used the client-supplied operation ID within its TTL
+pub fn get_operation_used(e: &Env, user: &Address, op_id: &BytesN<32>) -> bool {
+    e.storage()
+        .temporary()
+        .has(&StrategyStorageKey::Operation(user.clone(), op_id.clone()))
+}
+
+pub fn set_operation_used(e: &Env, user: &Address, op_id: &BytesN<32>) {
+    let key = StrategyStorageKey::Operation(user.clone(), op_id.clone());
+    e.storage()
+        .temporary()
+        .set::<StrategyStorageKey, bool>(&key, &true);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, OPERATION_TTL, OPERATION_TTL);
+}
+
+/// Returns the owner's share lock, which may have expired
+pub fn get_share_lock(e: &Env, owner: &Address) -> Option<ShareLock> {
+    let key = StrategyStorageKey::ShareLock(owner.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<StrategyStorageKey, ShareLock>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+    }
+    result
+}
+
+/// Stores the owner's share lock; `None` removes it
+pub fn set_share_lock(e: &Env, owner: &Address, lock: &Option<ShareLock>) {
+    let key = StrategyStorageKey::ShareLock(owner.clone());
+    let Some(lock) = lock else {
+        e.storage().persistent().remove(&key);
+        return;
+    };
+    e.storage()
+        .persistent()
+        .set::<StrategyStorageKey, ShareLock>(&key, lock);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+}
@@ -1,26 +1,545 @@
 //! Strategy integration and custom vault extensions
 
-use soroban_sdk::{contracterror, contractevent, panic_with_error, token, Address, Env};
-use stellar_tokens::vault::Vault;
+use soroban_sdk::{
+    contractclient, contracterror, contractevent, contracttype, panic_with_error, token,
+    unwrap::UnwrapOptimized, xdr::ToXdr, Address, BytesN, Env, Vec,
+};
+use stellar_contract_utils::math::{mul_div_i128, Rounding as MulDivRounding};
+use stellar_tokens::{
+    fungible::{emit_approve, Base},
+    vault::{emit_deposit, emit_withdraw, Vault, VaultTokenError},
+};
 
-use crate::storage;
+use crate::merkle;
+use crate::storage::{
+    self, AccountingSnapshot, AssetInfo, CouncilConfig, DepositCap, DepositCoupon, ExitFee,
+    IdleDeploy, LockTier, RedemptionRecord, Rounding, RoundingInfo, ShareLock, StrategyAction,
+    StrategyActivity, StrategyData, StrategyWithdrawRequest, UserPnl, VaultMetadata, WithdrawLimit,
+    WithdrawWindow,
+};
+
+/// Fixed-point scale for rates: 10_000_000 == 100%
+pub const SCALAR_7: i128 = 10_000_000;
+
+/// A fraction between 0% and 100% in SCALAR_7. Every setter builds its rate with `new`
+/// or `new_fee`, so stored rates are always within bounds.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct Rate(i128);
+
+impl Rate {
+    pub const MAX: Rate = Rate(SCALAR_7);
+
+    /// Panics with `InvalidConfiguration` unless `value` is within 0-100%
+    pub fn new(e: &Env, value: i128) -> Self {
+        if !(0..=SCALAR_7).contains(&value) {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        Rate(value)
+    }
+
+    /// Like `new`, but also rejects 100%, which would leave nothing after the fee
+    pub fn new_fee(e: &Env, value: i128) -> Self {
+        let rate = Self::new(e, value);
+        if rate == Self::MAX {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        rate
+    }
+
+    pub fn value(self) -> i128 {
+        self.0
+    }
+
+    // The helpers multiply through `mul_div_i128`, which widens to 256 bits where needed,
+    // so any amount works whose result fits in an i128, and a result that does not fit
+    // panics instead of wrapping.
+
+    /// Returns this fraction of `amount`, rounded down
+    pub fn of(self, e: &Env, amount: i128) -> i128 {
+        mul_div_i128(e, amount, self.0, SCALAR_7, MulDivRounding::Floor)
+    }
+
+    /// Returns the gross amount that leaves `net` after a fee at this rate, rounded up.
+    /// The rate must be below 100%.
+    pub fn gross_up(self, e: &Env, net: i128) -> i128 {
+        mul_div_i128(e, net, SCALAR_7, SCALAR_7 - self.0, MulDivRounding::Ceil)
+    }
+
+    /// Returns `numerator / denominator` of this rate, rounded down. The numerator must
+    /// not exceed the denominator.
+    pub fn scale(self, e: &Env, numerator: u64, denominator: u64) -> Self {
+        Rate(mul_div_i128(
+            e,
+            self.0,
+            i128::from(numerator),
+            i128::from(denominator),
+            MulDivRounding::Floor,
+        ))
+    }
+
+    /// Returns true if `part` is more than this fraction of `whole`. For a whole part
+    /// this is the same as exceeding the fraction rounded down.
+    pub fn is_exceeded(self, e: &Env, part: i128, whole: i128) -> bool {
+        part > self.of(e, whole)
+    }
+}
+
+/// Rounding of every conversion. Share conversions come from the OpenZeppelin vault and
+/// round against the caller, so no pair of operations can take value from the vault.
+/// Fees go through `Rate::of` and round down in the payer's favor, while the gross-up that
+/// lets a withdrawal cover its exit fee rounds up.
+pub const ROUNDING: RoundingInfo = RoundingInfo {
+    deposit: Rounding::Floor,
+    mint: Rounding::Ceil,
+    redeem: Rounding::Floor,
+    withdraw: Rounding::Ceil,
+    exit_fee: Rounding::Floor,
+    withdraw_gross_up: Rounding::Ceil,
+    profit_share: Rounding::Floor,
+    borrow_fee: Rounding::Floor,
+};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum StrategyVaultError {
+    /// An amount passed to an entrypoint is zero or negative
     InvalidAmount = 420,
+    /// The owner's shares are still within the deposit lock
     SharesLocked = 421,
+    /// The caller is not a registered strategy
     UnauthorizedStrategy = 422,
+    /// The first deposit is below the minimum or too small to cover the bootstrap shares
+    InitialDepositTooSmall = 423,
+    /// A configuration value is outside its allowed range
+    InvalidConfiguration = 424,
+    /// The caller is not the strategy's manager
+    UnauthorizedManager = 425,
+    /// The withdrawal would deploy more than the allowed fraction of vault assets
+    DeploymentCapExceeded = 426,
+    /// The withdrawal exceeds the capacity left in the current window
+    TryLater = 427,
+    /// The operation would move the share price more than the configured maximum
+    PriceDeviationExceeded = 428,
+    /// The strategy is already registered or scheduled
+    StrategyAlreadyAdded = 429,
+    /// No addition is scheduled for the strategy
+    StrategyNotPending = 430,
+    /// The strategy's activation time has not been reached
+    StrategyTimelocked = 431,
+    /// The withdrawal would exceed the strategy's exposure cap
+    StrategyExposureCapExceeded = 432,
+    /// The vault is winding down and no longer accepts deposits or strategy withdrawals
+    VaultWoundDown = 433,
+    /// The operation is only available once the vault is winding down
+    VaultNotWoundDown = 434,
+    /// The vault received fewer assets than required to mint the requested shares
+    TransferShortfall = 435,
+    /// The target vault manages a different underlying asset
+    AssetMismatch = 436,
+    /// Deposits are gated and the receiver is not proven to be on the allowlist
+    NotAllowlisted = 437,
+    /// The deposit coupon has expired
+    CouponExpired = 438,
+    /// The deposit would take the receiver past the coupon's maximum amount
+    CouponAmountExceeded = 439,
+    /// Coupons are not enabled
+    CouponsDisabled = 440,
+    /// The exit would take the share supply below the configured floor
+    SupplyFloorBreached = 441,
+    /// The deposit is too small to mint any shares at the current share price
+    ZeroSharesMinted = 442,
+    /// The redemption is too small to return any assets at the current share price
+    ZeroTokensReturned = 443,
+    /// The withdrawal is above the council threshold and must be requested
+    CouncilApprovalRequired = 444,
+    /// The strategy has no withdrawal request, or it has expired
+    WithdrawRequestNotFound = 445,
+    /// The vault itself and its underlying asset cannot be strategies
+    InvalidStrategy = 446,
+    /// The shares are escrowed for another spender with `approve_locked`
+    SharesEscrowed = 447,
+    /// The deposit would take managed assets above the launch schedule's cap
+    DepositCapExceeded = 448,
+    /// The shares were minted in the current ledger
+    SameLedgerExit = 449,
+    /// The operation ID was already used by this account
+    DuplicateOperation = 450,
+    /// Deposits are paused until a strategy reports on its deployed assets
+    DepositsPaused = 451,
+    /// Donations are only accepted once the vault has shares
+    VaultEmpty = 452,
+    /// The ledger timestamp is past the operation's deadline
+    Expired = 453,
+    /// The owner has no live share lock to release
+    NoActiveLock = 454,
+    /// An owner cannot escrow shares for themselves
+    SelfApproval = 455,
 }
-#[contractevent]
+
+// Events carry their schema version as the second topic; bump it together with
+// `client::EVENT_SCHEMA_VERSION` whenever an event's topics or payload change.
+#[contractevent(topics = ["strategy_withdraw", "v2"])]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StrategyWithdraw {
     #[topic]
     pub strategy: Address,
     pub amount: i128,
+    pub total_sent: i128,
+}
+
+#[contractevent(topics = ["strategy_deposit", "v2"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyDeposit {
+    #[topic]
+    pub strategy: Address,
+    pub amount: i128,
+    pub profit_share: i128,
+    pub total_received: i128,
+}
+
+/// Summary of a strategy return for allocator dashboards. Losses are not realized by
+/// the vault: unreturned principal stays outstanding as `debt`.
+#[contractevent(topics = ["strategy_report", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyReport {
+    #[topic]
+    pub strategy: Address,
+    /// Profit credited to depositors
+    pub gain: i128,
+    /// Loss written off by the vault; always 0, as the vault never realizes losses
+    pub loss: i128,
+    /// Profit kept by the strategy as its profit share
+    pub fees: i128,
+    /// Assets still deployed to the strategy (its negative net impact)
+    pub debt: i128,
+}
+
+#[contractevent(topics = ["profit_share_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProfitShareSet {
+    #[topic]
+    pub strategy: Address,
+    pub rate: i128,
+}
+
+#[contractevent(topics = ["borrow_fee_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BorrowFeeSet {
+    #[topic]
+    pub strategy: Address,
+    pub rate: i128,
+}
+
+#[contractevent(topics = ["borrow_fee_charged", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BorrowFeeCharged {
+    #[topic]
+    pub strategy: Address,
+    pub fee: i128,
+}
+
+#[contractevent(topics = ["strategy_manager_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyManagerSet {
+    #[topic]
+    pub strategy: Address,
+    pub manager: Option<Address>,
+}
+
+#[contractevent(topics = ["max_deployed_rate_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxDeployedRateSet {
+    pub rate: i128,
+}
+
+#[contractevent(topics = ["withdraw_limit_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawLimitSet {
+    pub limit: i128,
+    pub window: u64,
+}
+
+#[contractevent(topics = ["max_price_change_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxPriceChangeSet {
+    pub rate: i128,
+}
+
+#[contractevent(topics = ["price_override_granted", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceOverrideGranted {
+    #[topic]
+    pub strategy: Address,
+}
+
+#[contractevent(topics = ["strategy_scheduled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyScheduled {
+    #[topic]
+    pub strategy: Address,
+    pub activation_time: u64,
+}
+
+#[contractevent(topics = ["strategy_activated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyActivated {
+    #[topic]
+    pub strategy: Address,
+}
+
+#[contractevent(topics = ["strategy_migrated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyMigrated {
+    #[topic]
+    pub old_strategy: Address,
+    #[topic]
+    pub new_strategy: Address,
+}
+
+#[contractevent(topics = ["exposure_cap_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExposureCapSet {
+    #[topic]
+    pub strategy: Address,
+    pub cap: Option<i128>,
+}
+
+#[contractevent(topics = ["max_deployed_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxDeployedSet {
+    pub cap: Option<i128>,
+}
+
+#[contractevent(topics = ["min_supply_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MinSupplySet {
+    pub min_supply: i128,
+}
+
+#[contractevent(topics = ["council_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouncilSet {
+    pub council: Option<Address>,
+    pub threshold: i128,
+    pub window: u64,
+}
+
+#[contractevent(topics = ["idle_deploy_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdleDeploySet {
+    pub strategy: Option<Address>,
+    pub threshold: i128,
+}
+
+#[contractevent(topics = ["strategy_withdraw_requested", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyWithdrawRequested {
+    #[topic]
+    pub strategy: Address,
+    pub amount: i128,
+    pub expires_at: u64,
+}
+
+#[contractevent(topics = ["lock_tiers_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockTiersSet {
+    pub tiers: u32,
+}
+
+#[contractevent(topics = ["lock_exemption_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockExemptionSet {
+    #[topic]
+    pub account: Address,
+    pub exempt: bool,
+}
+
+#[contractevent(topics = ["strategy_payout_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyPayoutSet {
+    #[topic]
+    pub strategy: Address,
+    pub payout: Option<Address>,
+}
+
+#[contractevent(topics = ["share_lock_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareLockSet {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub spender: Address,
+    pub shares: i128,
+    pub expiration_ledger: u32,
+}
+
+#[contractevent(topics = ["share_lock_released", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareLockReleased {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub spender: Address,
+}
+
+#[contractevent(topics = ["donation", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Donation {
+    #[topic]
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["wind_down_started", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WindDownStarted {
+    pub total_assets: i128,
+    pub deployed: i128,
+}
+
+#[contractevent(topics = ["balance_delta_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceDeltaSet {
+    pub enabled: bool,
+}
+
+#[contractevent(topics = ["strategy_heartbeat", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyHeartbeat {
+    #[topic]
+    pub strategy: Address,
+}
+
+#[contractevent(topics = ["report_interval_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportIntervalSet {
+    pub interval: u64,
+}
+
+#[contractevent(topics = ["same_ledger_guard_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SameLedgerGuardSet {
+    pub enabled: bool,
+}
+
+#[contractevent(topics = ["operator_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OperatorSet {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub operator: Address,
+    pub approved: bool,
+}
+
+#[contractevent(topics = ["deposit_root_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositRootSet {
+    pub root: Option<BytesN<32>>,
+}
+
+#[contractevent(topics = ["coupon_signer_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouponSignerSet {
+    pub signer: Option<BytesN<32>>,
+}
+
+#[contractevent(topics = ["governor_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernorSet {
+    #[topic]
+    pub governor: Address,
+}
+
+#[contractevent(topics = ["exit_fee_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitFeeSet {
+    pub rate: i128,
+    pub duration: u64,
+}
+
+#[contractevent(topics = ["exit_fee_charged", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitFeeCharged {
+    #[topic]
+    pub owner: Address,
+    pub fee: i128,
+}
+
+#[contractevent(topics = ["receiver_hook_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiverHookSet {
+    #[topic]
+    pub receiver: Address,
+    pub enabled: bool,
+}
+
+#[contractevent(topics = ["metadata_updated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataUpdated {
+    pub metadata: VaultMetadata,
+}
+
+/// Interface a contract implements to be notified of vault deposits and redemptions.
+///
+/// Hooks are invoked after all vault state changes and token transfers have
+/// completed. The Soroban host rejects re-entry into a contract already on the
+/// call stack, so a hook cannot call back into the vault.
+#[allow(dead_code)]
+#[contractclient(name = "VaultReceiverClient")]
+pub trait VaultReceiver {
+    /// Called after `shares` were minted to the receiver in exchange for `assets`
+    fn on_vault_deposit(e: Env, vault: Address, from: Address, assets: i128, shares: i128);
+
+    /// Called after `assets` were sent to the receiver for `shares` burned from `owner`
+    fn on_vault_redeem(e: Env, vault: Address, owner: Address, assets: i128, shares: i128);
+}
+
+#[contractevent(topics = ["bootstrap_shares_locked", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootstrapSharesLocked {
+    #[topic]
+    pub depositor: Address,
+    pub shares: i128,
+}
+
+/// Shares taken from the first depositor and held by the vault forever, so the
+/// share supply can never return to zero and be re-priced by a donation.
+pub const BOOTSTRAP_SHARES: i128 = 1000;
+
+/// Subset of the vault interface used to look through a vault-share underlying
+#[allow(dead_code)]
+#[contractclient(name = "UnderlyingVaultClient")]
+pub trait UnderlyingVault {
+    fn query_asset(e: Env) -> Address;
+    fn convert_to_assets(e: Env, shares: i128) -> i128;
+}
+
+/// Subset of the vault interface used to deposit into another vault when migrating
+#[allow(dead_code)]
+#[contractclient(name = "TargetVaultClient")]
+pub trait TargetVault {
+    fn query_asset(e: Env) -> Address;
+    fn deposit(e: Env, assets: i128, receiver: Address, from: Address, operator: Address) -> i128;
+}
+
+#[contractevent(topics = ["position_migrated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionMigrated {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub new_vault: Address,
+    pub shares: i128,
+    pub assets: i128,
+    pub new_shares: i128,
 }
 
+/// Maximum number of nested vaults followed when resolving the base asset
+pub const MAX_VAULT_DEPTH: u32 = 4;
+
 pub struct StrategyVault;
 
 impl StrategyVault {
@@ -30,37 +549,1600 @@ impl StrategyVault {
         let Some(last_deposit_time) = storage::get_last_deposit_time(e, user) else {
             return 0;
         };
-        let unlock_time = last_deposit_time.saturating_add(storage::get_lock_time(e));
+        if storage::get_lock_exempt(e, user) {
+            return 0;
+        }
+        let unlock_time = last_deposit_time.saturating_add(storage::get_user_lock_time(e, user));
         unlock_time.saturating_sub(e.ledger().timestamp())
     }
 
+    /// Follows the chain of underlying vaults (if the underlying is itself a vault's
+    /// share token) and returns the base asset together with `assets` expressed in it
+    pub fn to_base_assets(e: &Env, assets: i128) -> (Address, i128) {
+        let mut asset = Vault::query_asset(e);
+        let mut amount = assets;
+        for _ in 0..MAX_VAULT_DEPTH {
+            let vault = UnderlyingVaultClient::new(e, &asset);
+            let Ok(Ok(inner)) = vault.try_query_asset() else {
+                break;
+            };
+            amount = vault.convert_to_assets(&amount);
+            asset = inner;
+        }
+        (asset, amount)
+    }
+
     /// Panics if user's shares are currently locked
     pub fn require_unlocked(e: &Env, user: &Address) {
+        Self::require_settled(e, user);
         if Self::get_lock_time(e, user) > 0 {
             panic_with_error!(e, StrategyVaultError::SharesLocked);
         }
     }
 
-    /// Strategy withdraws tokens from the vault
-    /// This decreases total_assets and thus the share price
-    pub fn withdraw(env: &Env, strategy: &Address, amount: i128) {
-        if amount <= 0 {
-            panic_with_error!(env, StrategyVaultError::InvalidAmount);
+    /// Returns true if the same-ledger guard holds the user's shares because they
+    /// deposited in the current ledger. Ledger close times strictly increase, so the
+    /// last deposit time identifies the ledger.
+    pub fn is_unsettled(e: &Env, user: &Address) -> bool {
+        storage::get_same_ledger_guard(e)
+            && storage::get_last_deposit_time(e, user) == Some(e.ledger().timestamp())
+    }
+
+    /// Panics if the user deposited in the current ledger while the guard is enabled
+    fn require_settled(e: &Env, user: &Address) {
+        if Self::is_unsettled(e, user) {
+            panic_with_error!(e, StrategyVaultError::SameLedgerExit);
         }
-        if !storage::get_strategies(env).contains(strategy) {
-            panic_with_error!(env, StrategyVaultError::UnauthorizedStrategy);
+    }
+
+    /// Enables or disables the guard against exiting in the ledger of a deposit
+    pub fn set_same_ledger_guard(e: &Env, enabled: bool) {
+        storage::set_same_ledger_guard(e, enabled);
+        SameLedgerGuardSet { enabled }.publish(e);
+    }
+
+    /// Returns true if no shares have been minted yet
+    pub fn is_first_deposit(e: &Env) -> bool {
+        Base::total_supply(e) == 0
+    }
+
+    /// Enforces the minimum first deposit and moves the bootstrap shares from the
+    /// receiver to the vault itself. Returns the shares left with the receiver.
+    pub fn lock_bootstrap_shares(e: &Env, receiver: &Address, assets: i128, shares: i128) -> i128 {
+        if assets < storage::get_min_initial_deposit(e) || shares <= BOOTSTRAP_SHARES {
+            panic_with_error!(e, StrategyVaultError::InitialDepositTooSmall);
         }
+        Base::update(
+            e,
+            Some(receiver),
+            Some(&e.current_contract_address()),
+            BOOTSTRAP_SHARES,
+        );
+        BootstrapSharesLocked {
+            depositor: receiver.clone(),
+            shares: BOOTSTRAP_SHARES,
+        }
+        .publish(e);
+        shares - BOOTSTRAP_SHARES
+    }
 
-        let asset = Vault::query_asset(env);
-        let token_client = token::Client::new(env, &asset);
+    /// Panics if the address is not a registered strategy
+    pub fn require_strategy(e: &Env, strategy: &Address) {
+        if !storage::get_strategies(e).contains(strategy) {
+            panic_with_error!(e, StrategyVaultError::UnauthorizedStrategy);
+        }
+    }
 
-        token_client.transfer(&env.current_contract_address(), strategy, &amount);
+    /// Returns the assets that can still be withdrawn in the current window
+    pub fn withdraw_capacity(e: &Env) -> i128 {
+        let config = storage::get_withdraw_limit(e);
+        if config.window == 0 {
+            return i128::MAX;
+        }
+        let window = storage::get_withdraw_window(e);
+        if e.ledger().timestamp() >= window.start.saturating_add(config.window) {
+            config.limit
+        } else {
+            (config.limit - window.used).max(0)
+        }
+    }
 
-        StrategyWithdraw {
+    /// Records a withdrawal against the current window, starting a new window once the
+    /// previous one has elapsed. Panics if the window's limit would be exceeded.
+    pub fn consume_withdraw_capacity(e: &Env, assets: i128) {
+        let config = storage::get_withdraw_limit(e);
+        if config.window == 0 {
+            return;
+        }
+        let now = e.ledger().timestamp();
+        let mut window = storage::get_withdraw_window(e);
+        if now >= window.start.saturating_add(config.window) {
+            window = WithdrawWindow {
+                start: now,
+                used: 0,
+            };
+        }
+        window.used += assets;
+        if window.used > config.limit {
+            panic_with_error!(e, StrategyVaultError::TryLater);
+        }
+        storage::set_withdraw_window(e, &window);
+    }
+
+    /// Sets the per-window withdrawal limit
+    pub fn set_withdraw_limit(e: &Env, limit: &WithdrawLimit) {
+        if limit.limit < 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        storage::set_withdraw_limit(e, limit);
+        WithdrawLimitSet {
+            limit: limit.limit,
+            window: limit.window,
+        }
+        .publish(e);
+    }
+
+    /// Panics if moving `delta` assets in or out would change the share price by more
+    /// than the configured maximum, unless the admin granted the strategy an override.
+    /// Strategy flows and donations never change the share supply, so the relative price
+    /// change is `delta / (total_assets + 1)` under the vault's conversion formula.
+    pub fn check_price_move(e: &Env, strategy: &Address, delta: i128) {
+        let max_change = storage::get_max_price_change(e);
+        if max_change == Rate::default() {
+            return;
+        }
+        let assets = Vault::total_assets(e) + 1;
+        if max_change.is_exceeded(e, delta, assets) && !storage::take_price_override(e, strategy) {
+            panic_with_error!(e, StrategyVaultError::PriceDeviationExceeded);
+        }
+    }
+
+    /// Sets the maximum share price change a single strategy operation may cause
+    pub fn set_max_price_change(e: &Env, rate: i128) {
+        storage::set_max_price_change(e, &Rate::new(e, rate));
+        MaxPriceChangeSet { rate }.publish(e);
+    }
+
+    /// Lets the strategy's next operation exceed the share price deviation limit
+    pub fn grant_price_override(e: &Env, strategy: &Address) {
+        Self::require_strategy(e, strategy);
+        storage::set_price_override(e, strategy);
+        PriceOverrideGranted {
             strategy: strategy.clone(),
-            amount,
         }
-        .publish(env);
+        .publish(e);
+    }
+
+    /// Enables or disables balance-delta accounting for fee-on-transfer underlyings
+    pub fn set_balance_delta(e: &Env, enabled: bool) {
+        storage::set_balance_delta(e, enabled);
+        BalanceDeltaSet { enabled }.publish(e);
+    }
+
+    /// Transfers `amount` from `from` to the vault, spending `operator`'s allowance if
+    /// it is not `from`, and returns the amount received. With balance-delta accounting
+    /// enabled this is measured from the vault's balance, so fees taken by the token
+    /// are not credited.
+    pub fn pull_assets(e: &Env, from: &Address, operator: &Address, amount: i128) -> i128 {
+        let token_client = token::Client::new(e, &Vault::query_asset(e));
+        let vault = e.current_contract_address();
+        let measure = storage::get_balance_delta(e);
+        let before = if measure {
+            token_client.balance(&vault)
+        } else {
+            0
+        };
+        if operator == from {
+            token_client.transfer(from, &vault, &amount);
+        } else {
+            token_client.transfer_from(operator, from, &vault, &amount);
+        }
+        if measure {
+            token_client.balance(&vault) - before
+        } else {
+            amount
+        }
+    }
+
+    /// Converts `assets` to shares with the vault's conversion formula and rounding, at
+    /// the share price given by `total_assets` instead of the live balance
+    fn shares_at(e: &Env, assets: i128, total_assets: i128) -> i128 {
+        let virtual_shares = 10_i128.pow(Vault::get_decimals_offset(e));
+        mul_div_i128(
+            e,
+            assets,
+            Base::total_supply(e) + virtual_shares,
+            total_assets + 1,
+            MulDivRounding::Floor,
+        )
+    }
+
+    /// Deposit path for balance-delta accounting: mints shares for the assets actually
+    /// received, priced against the vault's assets before the transfer. Returns the
+    /// assets received and the shares minted.
+    pub fn deposit_measured(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+    ) -> (i128, i128) {
+        operator.require_auth();
+        if assets <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        let total_assets = Vault::total_assets(e);
+        let received = Self::pull_assets(e, from, operator, assets);
+        let shares = Self::shares_at(e, received, total_assets);
+        Base::update(e, None, Some(receiver), shares);
+        emit_deposit(e, operator, from, receiver, received, shares);
+        (received, shares)
+    }
+
+    /// Mint path for balance-delta accounting: panics unless the vault received the full
+    /// amount of assets the shares are priced at. Returns the assets received.
+    pub fn mint_measured(
+        e: &Env,
+        shares: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+    ) -> i128 {
+        operator.require_auth();
+        let assets = Vault::preview_mint(e, shares);
+        let received = Self::pull_assets(e, from, operator, assets);
+        if received < assets {
+            panic_with_error!(e, StrategyVaultError::TransferShortfall);
+        }
+        Base::update(e, None, Some(receiver), shares);
+        emit_deposit(e, operator, from, receiver, received, shares);
+        received
     }
 
+    /// Panics if deposits are gated and `proof` does not show that `receiver` is on the
+    /// merkle allowlist. Deposits are gated by an allowlist root or a coupon signer;
+    /// holders of a coupon deposit with `deposit_with_coupon` instead, so a signer without
+    /// a root makes the vault invite-only.
+    pub fn require_allowlisted(e: &Env, receiver: &Address, proof: &Vec<BytesN<32>>) {
+        let root = storage::get_deposit_root(e);
+        if root.is_none() && storage::get_coupon_signer(e).is_none() {
+            return;
+        }
+        let proven =
+            root.is_some_and(|root| merkle::verify(e, &root, merkle::leaf(e, receiver), proof));
+        if !proven {
+            panic_with_error!(e, StrategyVaultError::NotAllowlisted);
+        }
+    }
+
+    /// Checks the coupon signer's signature over this vault and `coupon`, its expiry
+    /// and its remaining amount, and records `assets` as used
+    pub fn redeem_coupon(e: &Env, coupon: &DepositCoupon, signature: &BytesN<64>, assets: i128) {
+        let Some(signer) = storage::get_coupon_signer(e) else {
+            panic_with_error!(e, StrategyVaultError::CouponsDisabled);
+        };
+        let message = (e.current_contract_address(), coupon.clone()).to_xdr(e);
+        e.crypto().ed25519_verify(&signer, &message, signature);
+        if e.ledger().timestamp() > coupon.expiry {
+            panic_with_error!(e, StrategyVaultError::CouponExpired);
+        }
+        let used = storage::get_coupon_used(e, &coupon.receiver) + assets;
+        if used > coupon.max_amount {
+            panic_with_error!(e, StrategyVaultError::CouponAmountExceeded);
+        }
+        storage::set_coupon_used(e, &coupon.receiver, used);
+    }
+
+    /// Sets the ed25519 public key that signs deposit coupons; `None` disables coupons
+    pub fn set_coupon_signer(e: &Env, signer: &Option<BytesN<32>>) {
+        storage::set_coupon_signer(e, signer);
+        CouponSignerSet {
+            signer: signer.clone(),
+        }
+        .publish(e);
+    }
+
+    /// Sets the merkle root of the deposit allowlist; `None` opens deposits to everyone
+    pub fn set_deposit_root(e: &Env, root: &Option<BytesN<32>>) {
+        storage::set_deposit_root(e, root);
+        DepositRootSet { root: root.clone() }.publish(e);
+    }
+
+    /// Deposits `assets` from `from` and credits shares to `receiver`, applying the
+    /// wind-down check, balance-delta accounting, the bootstrap share lock, the deposit
+    /// lock and the receiver hook. Returns the assets received and the shares credited.
+    pub fn deposit_assets(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+    ) -> (i128, i128) {
+        Self::deposit_with_proof(e, assets, receiver, from, operator, &Vec::new(e))
+    }
+
+    /// `deposit_assets` for a receiver proven to be on the deposit allowlist
+    pub fn deposit_with_proof(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+        proof: &Vec<BytesN<32>>,
+    ) -> (i128, i128) {
+        Self::require_allowlisted(e, receiver, proof);
+        Self::deposit_admitted(e, assets, receiver, from, operator)
+    }
+
+    /// `deposit_assets` that records a client-supplied operation ID for `from` and fails
+    /// if the ID was already used, so retried submissions cannot deposit twice
+    pub fn deposit_with_id(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+        op_id: &BytesN<32>,
+    ) -> (i128, i128) {
+        if storage::get_operation_used(e, from, op_id) {
+            panic_with_error!(e, StrategyVaultError::DuplicateOperation);
+        }
+        storage::set_operation_used(e, from, op_id);
+        Self::deposit_assets(e, assets, receiver, from, operator)
+    }
+
+    /// Panics if the ledger timestamp is past `deadline`, so a transaction that sat in the
+    /// queue after quoting cannot execute at a stale share price
+    pub fn require_deadline(e: &Env, deadline: u64) {
+        if e.ledger().timestamp() > deadline {
+            panic_with_error!(e, StrategyVaultError::Expired);
+        }
+    }
+
+    /// `deposit_assets` for the receiver of a signed deposit coupon
+    pub fn deposit_with_coupon(
+        e: &Env,
+        assets: i128,
+        from: &Address,
+        operator: &Address,
+        coupon: &DepositCoupon,
+        signature: &BytesN<64>,
+    ) -> (i128, i128) {
+        Self::redeem_coupon(e, coupon, signature, assets);
+        Self::deposit_admitted(e, assets, &coupon.receiver, from, operator)
+    }
+
+    /// Deposit path shared by every entrypoint once the receiver has been admitted
+    fn deposit_admitted(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+    ) -> (i128, i128) {
+        Self::require_active(e);
+        Self::require_fresh_reports(e);
+        let first_deposit = Self::is_first_deposit(e);
+        let (assets, shares) = if storage::get_balance_delta(e) {
+            Self::deposit_measured(e, assets, receiver, from, operator)
+        } else {
+            let shares =
+                Vault::deposit(e, assets, receiver.clone(), from.clone(), operator.clone());
+            (assets, shares)
+        };
+        if shares == 0 {
+            panic_with_error!(e, StrategyVaultError::ZeroSharesMinted);
+        }
+        let shares = Self::settle_deposit(e, first_deposit, receiver, from, assets, shares);
+        (assets, shares)
+    }
+
+    /// Mints `shares` to `receiver` for assets from `from`, with the same checks as
+    /// `deposit_assets`. Returns the assets received and the shares credited.
+    pub fn mint_shares(
+        e: &Env,
+        shares: i128,
+        receiver: &Address,
+        from: &Address,
+        operator: &Address,
+    ) -> (i128, i128) {
+        Self::require_active(e);
+        Self::require_fresh_reports(e);
+        Self::require_allowlisted(e, receiver, &Vec::new(e));
+        let first_deposit = Self::is_first_deposit(e);
+        let assets = if storage::get_balance_delta(e) {
+            Self::mint_measured(e, shares, receiver, from, operator)
+        } else {
+            Vault::mint(e, shares, receiver.clone(), from.clone(), operator.clone())
+        };
+        let shares = Self::settle_deposit(e, first_deposit, receiver, from, assets, shares);
+        (assets, shares)
+    }
+
+    /// Returns the launch schedule's current cap on managed assets, or None if uncapped
+    pub fn deposit_cap(e: &Env) -> Option<i128> {
+        let cap = storage::get_deposit_cap(e)?;
+        let elapsed = e.ledger().timestamp().saturating_sub(cap.start);
+        let steps = elapsed.checked_div(cap.period).unwrap_or(0);
+        Some(
+            cap.initial
+                .saturating_add(cap.step.saturating_mul(i128::from(steps))),
+        )
+    }
+
+    /// Returns the assets that can still be deposited under the launch schedule's cap
+    pub fn deposit_room(e: &Env) -> i128 {
+        match Self::deposit_cap(e) {
+            Some(cap) => (cap - Vault::total_assets(e) - Self::deployed_assets(e)).max(0),
+            None => i128::MAX,
+        }
+    }
+
+    /// Panics if managed assets exceed the launch schedule's cap. Runs once the deposited
+    /// assets have arrived.
+    fn require_deposit_cap(e: &Env) {
+        let managed = Vault::total_assets(e) + Self::deployed_assets(e);
+        if matches!(Self::deposit_cap(e), Some(cap) if managed > cap) {
+            panic_with_error!(e, StrategyVaultError::DepositCapExceeded);
+        }
+    }
+
+    /// Stores the launch schedule for the cap on managed assets
+    pub fn set_deposit_cap(e: &Env, cap: &DepositCap) {
+        if cap.initial < 0 || cap.step < 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        storage::set_deposit_cap(e, cap);
+    }
+
+    /// Locks the bootstrap shares on the first deposit, starts the receiver's deposit
+    /// lock and notifies it. Returns the shares left with the receiver.
+    fn settle_deposit(
+        e: &Env,
+        first_deposit: bool,
+        receiver: &Address,
+        from: &Address,
+        assets: i128,
+        shares: i128,
+    ) -> i128 {
+        Self::require_deposit_cap(e);
+        let shares = if first_deposit {
+            Self::lock_bootstrap_shares(e, receiver, assets, shares)
+        } else {
+            shares
+        };
+        let mut stats = storage::get_stats(e);
+        stats.total_deposited += assets;
+        if storage::get_last_deposit_time(e, receiver).is_none() {
+            stats.depositors += 1;
+        }
+        storage::set_stats(e, &stats);
+        let mut pnl = storage::get_user_pnl(e, receiver);
+        pnl.cost_basis += assets;
+        storage::set_user_pnl(e, receiver, &pnl);
+        Self::start_clocks(e, receiver, from, assets);
+        Self::notify_deposit(e, receiver, from, assets, shares);
+        shares
+    }
+
+    /// Starts the receiver's lock and exit fee clocks for a deposit of `assets`. A deposit
+    /// the receiver pays for, directly or through their operator, restarts both. One paid
+    /// by someone else only starts a lock when none is running, and moves the fee clock
+    /// towards now by the deposit's share of the position, so nobody can keep another
+    /// holder locked or at the full fee while funded shares still cannot exit at once.
+    fn start_clocks(e: &Env, receiver: &Address, from: &Address, assets: i128) {
+        let now = e.ledger().timestamp();
+        if from == receiver || storage::get_operator(e, receiver, from) {
+            storage::set_last_deposit_time(e, receiver, now);
+            storage::set_fee_start_time(e, receiver, now);
+            Self::apply_lock_tier(e, receiver);
+            return;
+        }
+        if Self::get_lock_time(e, receiver) == 0 {
+            storage::set_last_deposit_time(e, receiver, now);
+            Self::apply_lock_tier(e, receiver);
+        }
+        let fee_start = match storage::get_fee_start_time(e, receiver) {
+            Some(start) => {
+                let value = Vault::convert_to_assets(e, Base::balance(e, receiver))
+                    .max(assets)
+                    .max(1);
+                let age = i128::from(now.saturating_sub(start));
+                start + (age * assets / value) as u64
+            }
+            None => now,
+        };
+        storage::set_fee_start_time(e, receiver, fee_start);
+    }
+
+    /// Picks the receiver's lock time from the highest tier their position reaches. Tiers
+    /// only ever lengthen the vault's lock time.
+    fn apply_lock_tier(e: &Env, receiver: &Address) {
+        let tiers = storage::get_lock_tiers(e);
+        if tiers.is_empty() {
+            return;
+        }
+        let value = Vault::convert_to_assets(e, Base::balance(e, receiver));
+        let base = storage::get_lock_time(e);
+        let lock_time = tiers
+            .iter()
+            .filter(|tier| value >= tier.min_assets)
+            .map(|tier| tier.lock_time)
+            .last()
+            .filter(|&lock_time| lock_time > base);
+        storage::set_user_lock_time(e, receiver, lock_time);
+    }
+
+    /// Adds `account` to or removes it from the lock exemption list
+    pub fn set_lock_exempt(e: &Env, account: &Address, exempt: bool) {
+        storage::set_lock_exempt(e, account, exempt);
+        LockExemptionSet {
+            account: account.clone(),
+            exempt,
+        }
+        .publish(e);
+    }
+
+    /// Sets the lock time tiers, sorted by strictly increasing `min_assets`
+    pub fn set_lock_tiers(e: &Env, tiers: &Vec<LockTier>) {
+        let mut previous: Option<LockTier> = None;
+        for tier in tiers.iter() {
+            let ordered = previous
+                .is_none_or(|p| tier.min_assets > p.min_assets && tier.lock_time >= p.lock_time);
+            if tier.min_assets < 0 || !ordered {
+                panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+            }
+            previous = Some(tier);
+        }
+        storage::set_lock_tiers(e, tiers);
+        LockTiersSet { tiers: tiers.len() }.publish(e);
+    }
+
+    /// Deposits the entire token balance of `from`, returning the shares credited
+    pub fn deposit_all(e: &Env, from: &Address, receiver: &Address) -> i128 {
+        let assets = token::Client::new(e, &Vault::query_asset(e)).balance(from);
+        if assets <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        Self::deposit_assets(e, assets, receiver, from, from).1
+    }
+
+    /// Returns the shares the owner can redeem, limited by the window's withdrawal
+    /// capacity and the share supply floor
+    pub fn max_redeem(e: &Env, owner: &Address) -> i128 {
+        if Self::is_unsettled(e, owner) {
+            return 0;
+        }
+        let capacity = Self::withdraw_capacity(e);
+        let balance = (Base::balance(e, owner) - Self::locked_shares(e, owner))
+            .min(Self::redeemable_supply(e));
+        if capacity == i128::MAX {
+            return balance;
+        }
+        balance.min(Vault::convert_to_shares(e, capacity))
+    }
+
+    /// Returns the shares that can leave the supply before it reaches the floor. The
+    /// floor does not apply once the vault is winding down.
+    pub fn redeemable_supply(e: &Env) -> i128 {
+        let min_supply = storage::get_min_supply(e);
+        if min_supply == 0 || storage::get_wind_down(e) {
+            return i128::MAX;
+        }
+        (Base::total_supply(e) - min_supply).max(0)
+    }
+
+    /// Panics if burning `shares` would take the share supply below the floor
+    fn require_supply_floor(e: &Env, shares: i128) {
+        if shares > Self::redeemable_supply(e) {
+            panic_with_error!(e, StrategyVaultError::SupplyFloorBreached);
+        }
+    }
+
+    /// Sets the share supply that redemptions may not go below, except during wind-down
+    pub fn set_min_supply(e: &Env, min_supply: i128) {
+        if min_supply < 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        storage::set_min_supply(e, min_supply);
+        MinSupplySet { min_supply }.publish(e);
+    }
+
+    /// Returns true if `operator` acts for `owner` through an operator approval rather
+    /// than a share allowance. Approved operators may only send assets to the owner.
+    fn is_approved_operator(
+        e: &Env,
+        receiver: &Address,
+        owner: &Address,
+        operator: &Address,
+    ) -> bool {
+        operator != owner && receiver == owner && storage::get_operator(e, owner, operator)
+    }
+
+    /// Burns `shares` from `owner` and sends `assets` to `receiver`. Approved operators
+    /// act without spending a share allowance.
+    fn exit(
+        e: &Env,
+        receiver: &Address,
+        owner: &Address,
+        operator: &Address,
+        assets: i128,
+        shares: i128,
+    ) {
+        operator.require_auth();
+        let spender = if Self::is_approved_operator(e, receiver, owner, operator) {
+            owner
+        } else {
+            operator
+        };
+        Vault::withdraw_internal(e, receiver, owner, assets, shares, spender);
+        emit_withdraw(e, operator, receiver, owner, assets, shares);
+    }
+
+    /// Sets whether `operator` may redeem and withdraw for `owner`
+    pub fn set_operator(e: &Env, owner: &Address, operator: &Address, approved: bool) {
+        storage::set_operator(e, owner, operator, approved);
+        OperatorSet {
+            owner: owner.clone(),
+            operator: operator.clone(),
+            approved,
+        }
+        .publish(e);
+    }
+
+    /// Returns the owner's share lock while it is live
+    pub fn share_lock(e: &Env, owner: &Address) -> Option<ShareLock> {
+        storage::get_share_lock(e, owner)
+            .filter(|lock| lock.expiration_ledger >= e.ledger().sequence())
+    }
+
+    /// Returns the shares the owner has escrowed with `approve_locked`
+    pub fn locked_shares(e: &Env, owner: &Address) -> i128 {
+        Self::share_lock(e, owner).map_or(0, |lock| lock.shares)
+    }
+
+    /// Approves `spender` for `shares` of the owner's balance and escrows them: until
+    /// the spender releases them or the lock expires, only the spender can move them.
+    /// An owner has at most one live lock.
+    pub fn approve_locked(
+        e: &Env,
+        owner: &Address,
+        spender: &Address,
+        shares: i128,
+        expiration_ledger: u32,
+    ) {
+        if spender == owner {
+            panic_with_error!(e, StrategyVaultError::SelfApproval);
+        }
+        if shares <= 0 || shares > Base::balance(e, owner) {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        if Self::share_lock(e, owner).is_some() {
+            panic_with_error!(e, StrategyVaultError::SharesEscrowed);
+        }
+        Base::set_allowance(e, owner, spender, shares, expiration_ledger);
+        emit_approve(e, owner, spender, shares, expiration_ledger);
+        storage::set_share_lock(
+            e,
+            owner,
+            &Some(ShareLock {
+                spender: spender.clone(),
+                shares,
+                expiration_ledger,
+            }),
+        );
+        ShareLockSet {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            shares,
+            expiration_ledger,
+        }
+        .publish(e);
+    }
+
+    /// Releases the owner's escrowed shares and revokes the spender's allowance
+    pub fn release_lock(e: &Env, owner: &Address) {
+        let Some(lock) = Self::share_lock(e, owner) else {
+            panic_with_error!(e, StrategyVaultError::NoActiveLock);
+        };
+        lock.spender.require_auth();
+        Base::set_allowance(e, owner, &lock.spender, 0, 0);
+        storage::set_share_lock(e, owner, &None);
+        ShareLockReleased {
+            owner: owner.clone(),
+            spender: lock.spender,
+        }
+        .publish(e);
+    }
+
+    /// Checks a move of `shares` out of the owner's balance against their share lock.
+    /// The lock's spender may take escrowed shares, which releases them; anyone else
+    /// may only move the unescrowed rest of the balance.
+    pub fn spend_escrow(e: &Env, owner: &Address, spender: &Address, shares: i128) {
+        let Some(mut lock) = Self::share_lock(e, owner) else {
+            return;
+        };
+        if spender == &lock.spender {
+            lock.shares -= shares.min(lock.shares);
+            let lock = Some(lock).filter(|lock| lock.shares > 0);
+            storage::set_share_lock(e, owner, &lock);
+        } else if shares > Base::balance(e, owner) - lock.shares {
+            panic_with_error!(e, StrategyVaultError::SharesEscrowed);
+        }
+    }
+
+    /// Panics if an allowance change would leave the lock's spender unable to take the
+    /// escrowed shares
+    pub fn require_lock_allowance(e: &Env, owner: &Address, spender: &Address, amount: i128) {
+        if matches!(Self::share_lock(e, owner), Some(lock) if &lock.spender == spender && amount < lock.shares)
+        {
+            panic_with_error!(e, StrategyVaultError::SharesEscrowed);
+        }
+    }
+
+    /// Hands control of the fee parameters to `governor`
+    pub fn set_governor(e: &Env, governor: &Address) {
+        storage::set_governor(e, governor);
+        GovernorSet {
+            governor: governor.clone(),
+        }
+        .publish(e);
+    }
+
+    /// Returns the exit fee rate the owner currently pays. It decays linearly from the
+    /// configured rate to zero over the fee duration, counted from the holder's fee clock
+    /// (see `start_clocks`). Nobody pays it once the vault is winding down.
+    pub fn exit_fee_rate(e: &Env, owner: &Address) -> Rate {
+        let config = storage::get_exit_fee(e);
+        if config.rate == Rate::default() || config.duration == 0 || storage::get_wind_down(e) {
+            return Rate::default();
+        }
+        let Some(fee_start_time) = storage::get_fee_start_time(e, owner) else {
+            return Rate::default();
+        };
+        if storage::get_lock_exempt(e, owner) {
+            return Rate::default();
+        }
+        let elapsed = e.ledger().timestamp().saturating_sub(fee_start_time);
+        let remaining = config.duration.saturating_sub(elapsed);
+        config.rate.scale(e, remaining, config.duration)
+    }
+
+    /// Sets the exit fee charged right after a deposit and the time it takes to decay
+    pub fn set_exit_fee(e: &Env, fee: &ExitFee) {
+        Rate::new_fee(e, fee.rate.value());
+        storage::set_exit_fee(e, fee);
+        ExitFeeSet {
+            rate: fee.rate.value(),
+            duration: fee.duration,
+        }
+        .publish(e);
+    }
+
+    /// Returns the part of the owner's cost basis carried by `shares` of their balance
+    fn cost_of(e: &Env, owner: &Address, pnl: &UserPnl, shares: i128) -> i128 {
+        let balance = Base::balance(e, owner);
+        if balance == 0 {
+            return 0;
+        }
+        pnl.cost_basis * shares.min(balance) / balance
+    }
+
+    /// Realizes the profit or loss of an exit burning `shares` for `assets`. Must run
+    /// before the shares are burned.
+    fn realize_pnl(e: &Env, owner: &Address, shares: i128, assets: i128) {
+        let mut pnl = storage::get_user_pnl(e, owner);
+        let cost = Self::cost_of(e, owner, &pnl, shares);
+        pnl.cost_basis -= cost;
+        pnl.realized += assets - cost;
+        storage::set_user_pnl(e, owner, &pnl);
+    }
+
+    /// Moves the cost basis of `amount` transferred shares from `from` to `to`. Must
+    /// run before the shares move.
+    pub fn transfer_cost_basis(e: &Env, from: &Address, to: &Address, amount: i128) {
+        if from == to {
+            return;
+        }
+        let mut from_pnl = storage::get_user_pnl(e, from);
+        let cost = Self::cost_of(e, from, &from_pnl, amount);
+        if cost == 0 {
+            return;
+        }
+        from_pnl.cost_basis -= cost;
+        storage::set_user_pnl(e, from, &from_pnl);
+        let mut to_pnl = storage::get_user_pnl(e, to);
+        to_pnl.cost_basis += cost;
+        storage::set_user_pnl(e, to, &to_pnl);
+    }
+
+    /// Records a payout to a holder in the stats and their redemption history, and
+    /// publishes the exit fee it paid, if any
+    fn record_exit(e: &Env, owner: &Address, shares: i128, assets: i128, fee: i128) {
+        let mut stats = storage::get_stats(e);
+        stats.total_withdrawn += assets;
+        stats.exit_fees += fee;
+        storage::set_stats(e, &stats);
+        storage::push_redemption(
+            e,
+            owner,
+            RedemptionRecord {
+                shares,
+                assets,
+                exit_fee: fee,
+                timestamp: e.ledger().timestamp(),
+            },
+        );
+        if fee > 0 {
+            ExitFeeCharged {
+                owner: owner.clone(),
+                fee,
+            }
+            .publish(e);
+        }
+    }
+
+    /// Returns the assets the owner can withdraw after the exit fee, limited by the
+    /// window's withdrawal capacity and the share supply floor
+    pub fn max_withdraw(e: &Env, owner: &Address) -> i128 {
+        if Self::is_unsettled(e, owner) {
+            return 0;
+        }
+        let mut gross =
+            Vault::preview_redeem(e, Base::balance(e, owner) - Self::locked_shares(e, owner));
+        let redeemable = Self::redeemable_supply(e);
+        if redeemable != i128::MAX {
+            gross = gross.min(Vault::preview_redeem(e, redeemable));
+        }
+        let fee = Self::exit_fee_rate(e, owner).of(e, gross);
+        (gross - fee).min(Self::withdraw_capacity(e))
+    }
+
+    /// Redeems `shares` from `owner` net of the exit fee, counting the assets against the
+    /// withdrawal limit and notifying the receiver. Returns the assets sent.
+    pub fn redeem_shares(
+        e: &Env,
+        shares: i128,
+        receiver: &Address,
+        owner: &Address,
+        operator: &Address,
+    ) -> i128 {
+        if shares > Vault::max_redeem(e, owner.clone()) {
+            panic_with_error!(e, VaultTokenError::VaultExceededMaxRedeem);
+        }
+        let gross = Vault::preview_redeem(e, shares);
+        let fee = Self::exit_fee_rate(e, owner).of(e, gross);
+        let assets = gross - fee;
+        if assets == 0 && shares > 0 {
+            panic_with_error!(e, StrategyVaultError::ZeroTokensReturned);
+        }
+        Self::require_supply_floor(e, shares);
+        Self::require_settled(e, owner);
+        Self::spend_escrow(e, owner, operator, shares);
+        Self::realize_pnl(e, owner, shares, assets);
+        Self::exit(e, receiver, owner, operator, assets, shares);
+        Self::record_exit(e, owner, shares, assets, fee);
+        Self::consume_withdraw_capacity(e, assets);
+        Self::notify_redeem(e, receiver, owner, assets, shares);
+        assets
+    }
+
+    /// Withdraws `assets` for `owner`, burning extra shares to cover the exit fee,
+    /// counting the assets against the withdrawal limit and notifying the receiver.
+    /// Returns the shares burned.
+    pub fn withdraw_assets(
+        e: &Env,
+        assets: i128,
+        receiver: &Address,
+        owner: &Address,
+        operator: &Address,
+    ) -> i128 {
+        // Gross up so that the fee on the gross amount leaves exactly `assets`
+        let gross = Self::exit_fee_rate(e, owner).gross_up(e, assets);
+        if gross > Vault::max_withdraw(e, owner.clone()) {
+            panic_with_error!(e, VaultTokenError::VaultExceededMaxWithdraw);
+        }
+        let shares = Vault::preview_withdraw(e, gross);
+        Self::require_supply_floor(e, shares);
+        Self::require_settled(e, owner);
+        Self::spend_escrow(e, owner, operator, shares);
+        Self::realize_pnl(e, owner, shares, assets);
+        Self::exit(e, receiver, owner, operator, assets, shares);
+        Self::record_exit(e, owner, shares, assets, gross - assets);
+        Self::consume_withdraw_capacity(e, assets);
+        Self::notify_redeem(e, receiver, owner, assets, shares);
+        shares
+    }
+
+    /// Redeems as many of the owner's shares as the withdrawal limit allows
+    pub fn redeem_max(e: &Env, receiver: &Address, owner: &Address) -> i128 {
+        let shares = Self::max_redeem(e, owner);
+        if shares <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        Self::redeem_shares(e, shares, receiver, owner, owner)
+    }
+
+    /// Redeems all of the owner's shares once the vault is winding down, bypassing the
+    /// withdrawal limit. Shares escrowed with `approve_locked` stay with the lock: its
+    /// spender redeems them, or the owner claims them once the lock is released or
+    /// expires. Returns the assets sent to the owner.
+    pub fn claim_wind_down(e: &Env, owner: &Address) -> i128 {
+        if !storage::get_wind_down(e) {
+            panic_with_error!(e, StrategyVaultError::VaultNotWoundDown);
+        }
+        let shares = Base::balance(e, owner) - Self::locked_shares(e, owner);
+        if shares == 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        let assets = Vault::preview_redeem(e, shares);
+        if assets == 0 {
+            panic_with_error!(e, StrategyVaultError::ZeroTokensReturned);
+        }
+        Self::realize_pnl(e, owner, shares, assets);
+        let assets = Vault::redeem(e, shares, owner.clone(), owner.clone(), owner.clone());
+        Self::record_exit(e, owner, shares, assets, 0);
+        Self::notify_redeem(e, owner, owner, assets, shares);
+        assets
+    }
+
+    /// Returns true while any strategy holding deployed assets has not reported or sent
+    /// a heartbeat within the report interval. Deposits and mints are rejected until
+    /// every stale strategy reports.
+    pub fn is_degraded(e: &Env) -> bool {
+        let interval = storage::get_report_interval(e);
+        interval > 0
+            && storage::get_strategies(e).iter().any(|strategy| {
+                storage::get_strategy_data(e, &strategy).net_impact < 0
+                    && e.ledger().timestamp()
+                        > storage::get_last_report(e, &strategy).saturating_add(interval)
+            })
+    }
+
+    /// Panics if deposits are paused for stale strategy reports
+    fn require_fresh_reports(e: &Env) {
+        if Self::is_degraded(e) {
+            panic_with_error!(e, StrategyVaultError::DepositsPaused);
+        }
+    }
+
+    /// Records a strategy heartbeat, which counts as a report without moving funds
+    pub fn heartbeat(e: &Env, strategy: &Address) {
+        Self::require_strategy(e, strategy);
+        storage::set_last_report(e, strategy, e.ledger().timestamp());
+        StrategyHeartbeat {
+            strategy: strategy.clone(),
+        }
+        .publish(e);
+    }
+
+    /// Sets how long strategies may go without reporting before deposits pause; 0
+    /// disables the check. Every strategy's interval counts from now until its next
+    /// report.
+    pub fn set_report_interval(e: &Env, interval: u64) {
+        storage::set_report_interval(e, interval);
+        for strategy in storage::get_strategies(e).iter() {
+            storage::set_last_report(e, &strategy, e.ledger().timestamp());
+        }
+        ReportIntervalSet { interval }.publish(e);
+    }
+
+    /// Panics if the vault is winding down
+    pub fn require_active(e: &Env) {
+        if storage::get_wind_down(e) {
+            panic_with_error!(e, StrategyVaultError::VaultWoundDown);
+        }
+    }
+
+    /// Permanently halts deposits and strategy withdrawals. Strategies return their
+    /// funds with `deposit`, and holders exit with `claim_wind_down`.
+    pub fn wind_down(e: &Env) {
+        Self::require_active(e);
+        storage::set_wind_down(e);
+        WindDownStarted {
+            total_assets: Vault::total_assets(e),
+            deployed: Self::deployed_assets(e),
+        }
+        .publish(e);
+    }
+
+    /// Strategy withdraws tokens from the vault
+    /// This decreases total_assets and thus the share price. Withdrawals that take the
+    /// strategy past the council threshold within the council window must first be
+    /// requested and approved by the council.
+    pub fn withdraw(env: &Env, strategy: &Address, amount: i128) {
+        Self::charge_council_budget(env, strategy, amount);
+        Self::send_to_strategy(env, strategy, amount);
+    }
+
+    /// Returns what the strategy can still withdraw without council approval in its
+    /// current window
+    pub fn council_budget(e: &Env, strategy: &Address) -> i128 {
+        let Some(config) = storage::get_council(e) else {
+            return i128::MAX;
+        };
+        let window = storage::get_council_window(e, strategy);
+        if e.ledger().timestamp() >= window.start.saturating_add(config.window) {
+            config.threshold
+        } else {
+            (config.threshold - window.used).max(0)
+        }
+    }
+
+    /// Records an unapproved withdrawal against the strategy's council window, starting a
+    /// new window once the previous one has elapsed. Panics if the window's threshold
+    /// would be exceeded.
+    fn charge_council_budget(e: &Env, strategy: &Address, amount: i128) {
+        let Some(config) = storage::get_council(e) else {
+            return;
+        };
+        let now = e.ledger().timestamp();
+        let mut window = storage::get_council_window(e, strategy);
+        if now >= window.start.saturating_add(config.window) {
+            window = WithdrawWindow {
+                start: now,
+                used: 0,
+            };
+        }
+        window.used += amount;
+        if window.used > config.threshold {
+            panic_with_error!(e, StrategyVaultError::CouncilApprovalRequired);
+        }
+        storage::set_council_window(e, strategy, &window);
+    }
+
+    /// Sets the council that approves large strategy withdrawals; `None` removes it
+    pub fn set_council(e: &Env, config: &Option<CouncilConfig>) {
+        if matches!(config, Some(config) if config.threshold < 0 || config.window == 0) {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        storage::set_council(e, config);
+        CouncilSet {
+            council: config.as_ref().map(|config| config.council.clone()),
+            threshold: config.as_ref().map_or(0, |config| config.threshold),
+            window: config.as_ref().map_or(0, |config| config.window),
+        }
+        .publish(e);
+    }
+
+    /// Sets the default strategy that idle assets above the threshold are deployed to;
+    /// `None` disables `deploy_idle`
+    pub fn set_idle_deploy(e: &Env, config: &Option<IdleDeploy>) {
+        if let Some(config) = config {
+            Self::require_strategy(e, &config.strategy);
+            if config.threshold < 0 {
+                panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+            }
+        }
+        storage::set_idle_deploy(e, config);
+        IdleDeploySet {
+            strategy: config.as_ref().map(|config| config.strategy.clone()),
+            threshold: config.as_ref().map_or(0, |config| config.threshold),
+        }
+        .publish(e);
+    }
+
+    /// Sends the idle assets above the threshold to the default strategy, subject to the
+    /// same caps as a strategy withdrawal. With a council, the push draws on the strategy's
+    /// council budget, so repeated calls move no more per window than the strategy could
+    /// withdraw itself. Returns the assets deployed.
+    pub fn deploy_idle(e: &Env) -> i128 {
+        let Some(config) = storage::get_idle_deploy(e) else {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        };
+        let surplus = Vault::total_assets(e) - config.threshold;
+        let budget = Self::council_budget(e, &config.strategy);
+        if surplus > 0 && budget == 0 {
+            panic_with_error!(e, StrategyVaultError::CouncilApprovalRequired);
+        }
+        let amount = surplus.min(budget);
+        Self::charge_council_budget(e, &config.strategy, amount);
+        Self::send_to_strategy(e, &config.strategy, amount);
+        amount
+    }
+
+    /// Records a withdrawal for the council to approve within its window, replacing
+    /// any earlier request from the strategy
+    pub fn request_withdraw(e: &Env, strategy: &Address, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        Self::require_strategy(e, strategy);
+        let Some(config) = storage::get_council(e) else {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        };
+        let request = StrategyWithdrawRequest {
+            amount,
+            expires_at: e.ledger().timestamp().saturating_add(config.window),
+        };
+        storage::set_withdraw_request(e, strategy, &request);
+        StrategyWithdrawRequested {
+            strategy: strategy.clone(),
+            amount,
+            expires_at: request.expires_at,
+        }
+        .publish(e);
+    }
+
+    /// Executes the strategy's pending withdrawal request on council approval
+    pub fn approve_withdraw(e: &Env, strategy: &Address) {
+        let request = storage::get_withdraw_request(e, strategy);
+        let Some(request) = request.filter(|r| e.ledger().timestamp() <= r.expires_at) else {
+            panic_with_error!(e, StrategyVaultError::WithdrawRequestNotFound);
+        };
+        storage::remove_withdraw_request(e, strategy);
+        Self::send_to_strategy(e, strategy, request.amount);
+    }
+
+    /// Transfers `amount` to the strategy after the deployment caps and circuit breaker
+    fn send_to_strategy(env: &Env, strategy: &Address, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(env, StrategyVaultError::InvalidAmount);
+        }
+        Self::require_active(env);
+        Self::require_strategy(env, strategy);
+
+        let mut data = storage::get_strategy_data(env, strategy);
+        // A newly opened position counts as reported until the report interval passes
+        if data.net_impact >= 0 {
+            storage::set_last_report(env, strategy, env.ledger().timestamp());
+        }
+        data.total_sent += amount;
+        data.net_impact = data.total_received - data.total_sent;
+        if matches!(data.exposure_cap, Some(cap) if -data.net_impact > cap) {
+            panic_with_error!(env, StrategyVaultError::StrategyExposureCapExceeded);
+        }
+        storage::set_strategy_data(env, strategy, &data);
+
+        // Cap what is deployed across all strategies, in absolute terms and as a
+        // fraction of managed assets
+        let deployed = Self::deployed_assets(env);
+        let managed = Vault::total_assets(env) - amount + deployed;
+        if storage::get_max_deployed_rate(env).is_exceeded(env, deployed, managed)
+            || matches!(storage::get_max_deployed(env), Some(cap) if deployed > cap)
+        {
+            panic_with_error!(env, StrategyVaultError::DeploymentCapExceeded);
+        }
+        Self::check_price_move(env, strategy, amount);
+
+        let asset = Vault::query_asset(env);
+        let token_client = token::Client::new(env, &asset);
+
+        // The borrow fee is withheld from the transfer while the strategy owes the full
+        // amount, so depositors earn it immediately
+        let fee = data.borrow_fee.of(env, amount);
+        token_client.transfer(&env.current_contract_address(), strategy, &(amount - fee));
+        Self::record_activity(env, strategy, StrategyAction::Withdraw, amount, &data);
+
+        StrategyWithdraw {
+            strategy: strategy.clone(),
+            amount,
+            total_sent: data.total_sent,
+        }
+        .publish(env);
+        if fee > 0 {
+            let mut stats = storage::get_stats(env);
+            stats.borrow_fees += fee;
+            storage::set_stats(env, &stats);
+            BorrowFeeCharged {
+                strategy: strategy.clone(),
+                fee,
+            }
+            .publish(env);
+        }
+    }
+
+    /// Adds a strategy flow to the recent activity buffer
+    fn record_activity(
+        e: &Env,
+        strategy: &Address,
+        action: StrategyAction,
+        amount: i128,
+        data: &StrategyData,
+    ) {
+        storage::push_recent_activity(
+            e,
+            StrategyActivity {
+                strategy: strategy.clone(),
+                action,
+                amount,
+                net_impact: data.net_impact,
+                timestamp: e.ledger().timestamp(),
+            },
+        );
+    }
+
+    /// Panics if `strategy` is the vault itself or its underlying asset
+    fn require_valid_strategy(e: &Env, strategy: &Address) {
+        if *strategy == e.current_contract_address() || *strategy == Vault::query_asset(e) {
+            panic_with_error!(e, StrategyVaultError::InvalidStrategy);
+        }
+    }
+
+    /// Validates and stores the strategies the vault is deployed with. Like an activated
+    /// strategy, each starts with a zero exposure cap.
+    pub fn set_initial_strategies(e: &Env, strategies: &Vec<Address>) {
+        for (i, strategy) in strategies.iter().enumerate() {
+            Self::require_valid_strategy(e, &strategy);
+            if strategies.first_index_of(&strategy) != Some(i as u32) {
+                panic_with_error!(e, StrategyVaultError::StrategyAlreadyAdded);
+            }
+            let mut data = storage::get_strategy_data(e, &strategy);
+            data.exposure_cap = Some(0);
+            storage::set_strategy_data(e, &strategy, &data);
+        }
+        storage::set_strategies(e, strategies);
+    }
+
+    /// Schedules a strategy to become active after the configured strategy delay
+    pub fn schedule_strategy(e: &Env, strategy: &Address) {
+        Self::require_valid_strategy(e, strategy);
+        let mut pending = storage::get_pending_strategies(e);
+        if storage::get_strategies(e).contains(strategy) || pending.contains_key(strategy.clone()) {
+            panic_with_error!(e, StrategyVaultError::StrategyAlreadyAdded);
+        }
+        let activation_time = e
+            .ledger()
+            .timestamp()
+            .saturating_add(storage::get_strategy_delay(e));
+        pending.set(strategy.clone(), activation_time);
+        storage::set_pending_strategies(e, &pending);
+        StrategyScheduled {
+            strategy: strategy.clone(),
+            activation_time,
+        }
+        .publish(e);
+    }
+
+    /// Removes a scheduled strategy from the pending set, panicking if it was not
+    /// scheduled or its activation time has not been reached
+    fn take_activatable(e: &Env, strategy: &Address) {
+        let mut pending = storage::get_pending_strategies(e);
+        let Some(activation_time) = pending.get(strategy.clone()) else {
+            panic_with_error!(e, StrategyVaultError::StrategyNotPending);
+        };
+        if e.ledger().timestamp() < activation_time {
+            panic_with_error!(e, StrategyVaultError::StrategyTimelocked);
+        }
+        pending.remove(strategy.clone());
+        storage::set_pending_strategies(e, &pending);
+    }
+
+    /// Registers a scheduled strategy once its activation time has passed. The new
+    /// strategy starts with a zero exposure cap, so it can only withdraw what it has
+    /// returned to the vault until the admin raises or lifts the cap.
+    pub fn activate_strategy(e: &Env, strategy: &Address) {
+        Self::take_activatable(e, strategy);
+        let mut strategies = storage::get_strategies(e);
+        strategies.push_back(strategy.clone());
+        storage::set_strategies(e, &strategies);
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.exposure_cap = Some(0);
+        storage::set_strategy_data(e, strategy, &data);
+        StrategyActivated {
+            strategy: strategy.clone(),
+        }
+        .publish(e);
+    }
+
+    /// Moves an active strategy's accounting, pending withdrawal request, council window,
+    /// price override and last report time to a redeployed address, repoints idle
+    /// deployment at it and deauthorizes the old one. The new address must have been
+    /// scheduled and passed the timelock.
+    pub fn migrate_strategy(e: &Env, old_strategy: &Address, new_strategy: &Address) {
+        Self::require_strategy(e, old_strategy);
+        Self::take_activatable(e, new_strategy);
+
+        let mut strategies = storage::get_strategies(e);
+        let index = strategies.first_index_of(old_strategy).unwrap_optimized();
+        strategies.set(index, new_strategy.clone());
+        storage::set_strategies(e, &strategies);
+
+        let data = storage::get_strategy_data(e, old_strategy);
+        storage::remove_strategy_data(e, old_strategy);
+        storage::set_strategy_data(e, new_strategy, &data);
+        if storage::take_price_override(e, old_strategy) {
+            storage::set_price_override(e, new_strategy);
+        }
+        if let Some(request) = storage::get_withdraw_request(e, old_strategy) {
+            storage::remove_withdraw_request(e, old_strategy);
+            storage::set_withdraw_request(e, new_strategy, &request);
+        }
+        let council_window = storage::get_council_window(e, old_strategy);
+        storage::remove_council_window(e, old_strategy);
+        storage::set_council_window(e, new_strategy, &council_window);
+        let last_report = storage::get_last_report(e, old_strategy);
+        storage::remove_last_report(e, old_strategy);
+        storage::set_last_report(e, new_strategy, last_report);
+
+        StrategyMigrated {
+            old_strategy: old_strategy.clone(),
+            new_strategy: new_strategy.clone(),
+        }
+        .publish(e);
+        let idle_deploy = storage::get_idle_deploy(e);
+        if let Some(config) = idle_deploy.filter(|config| config.strategy == *old_strategy) {
+            let config = IdleDeploy {
+                strategy: new_strategy.clone(),
+                threshold: config.threshold,
+            };
+            Self::set_idle_deploy(e, &Some(config));
+        }
+    }
+
+    /// Collects supply, assets, strategy exposure and the share price in one struct
+    pub fn accounting_snapshot(e: &Env) -> AccountingSnapshot {
+        let (deployed, net_impact) =
+            storage::get_strategies(e)
+                .iter()
+                .fold((0, 0), |(deployed, net_impact), strategy| {
+                    let impact = storage::get_strategy_data(e, &strategy).net_impact;
+                    (deployed + (-impact).max(0), net_impact + impact)
+                });
+        AccountingSnapshot {
+            total_supply: Base::total_supply(e),
+            total_assets: Vault::total_assets(e),
+            deployed,
+            net_impact,
+            share_price: Vault::convert_to_assets(e, 10_i128.pow(Vault::decimals(e))),
+            timestamp: e.ledger().timestamp(),
+        }
+    }
+
+    /// Collects the underlying and share token details in one struct
+    pub fn asset_info(e: &Env) -> AssetInfo {
+        let asset = Vault::query_asset(e);
+        AssetInfo {
+            asset_decimals: token::Client::new(e, &asset).decimals(),
+            asset,
+            share_token: e.current_contract_address(),
+            share_name: Base::name(e),
+            share_symbol: Base::symbol(e),
+            share_decimals: Vault::decimals(e),
+            share_price: Vault::convert_to_assets(e, 10_i128.pow(Vault::decimals(e))),
+        }
+    }
+
+    /// Returns the assets currently deployed across strategies: the sum of each
+    /// strategy's outstanding withdrawals (negative net impact)
+    pub fn deployed_assets(e: &Env) -> i128 {
+        storage::get_strategies(e)
+            .iter()
+            .map(|strategy| (-storage::get_strategy_data(e, &strategy).net_impact).max(0))
+            .sum()
+    }
+
+    /// Sets the maximum fraction of managed assets that may be deployed to strategies
+    pub fn set_max_deployed_rate(e: &Env, rate: i128) {
+        storage::set_max_deployed_rate(e, Rate::new(e, rate));
+        MaxDeployedRateSet { rate }.publish(e);
+    }
+
+    /// Sets the absolute cap on assets deployed across all strategies
+    pub fn set_max_deployed(e: &Env, cap: Option<i128>) {
+        if matches!(cap, Some(cap) if cap < 0) {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        storage::set_max_deployed(e, &cap);
+        MaxDeployedSet { cap }.publish(e);
+    }
+
+    /// Sets the cap on a strategy's outstanding withdrawals
+    pub fn set_exposure_cap(e: &Env, strategy: &Address, cap: Option<i128>) {
+        Self::require_strategy(e, strategy);
+        if matches!(cap, Some(cap) if cap < 0) {
+            panic_with_error!(e, StrategyVaultError::InvalidConfiguration);
+        }
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.exposure_cap = cap;
+        storage::set_strategy_data(e, strategy, &data);
+        ExposureCapSet {
+            strategy: strategy.clone(),
+            cap,
+        }
+        .publish(e);
+    }
+
+    /// Panics if `manager` is not the strategy's configured manager
+    pub fn require_manager(e: &Env, strategy: &Address, manager: &Address) {
+        Self::require_strategy(e, strategy);
+        if storage::get_strategy_data(e, strategy).manager.as_ref() != Some(manager) {
+            panic_with_error!(e, StrategyVaultError::UnauthorizedManager);
+        }
+    }
+
+    /// Returns tokens from `from` to the vault on behalf of a strategy
+    /// This increases total_assets and thus the share price. The part of `amount` that
+    /// lifts the strategy's net impact above zero is profit, of which the strategy keeps
+    /// its configured share. The gain reported to depositors is what the vault actually
+    /// received above zero net impact.
+    pub fn deposit(e: &Env, strategy: &Address, from: &Address, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        Self::require_strategy(e, strategy);
+
+        let mut data = storage::get_strategy_data(e, strategy);
+        let profit = (data.net_impact + amount).max(0) - data.net_impact.max(0);
+        let profit_share = data.profit_share.of(e, profit);
+        let credited = amount - profit_share;
+        Self::check_price_move(e, strategy, credited);
+        if profit_share > 0 {
+            let mut stats = storage::get_stats(e);
+            stats.strategy_fees += profit_share;
+            storage::set_stats(e, &stats);
+        }
+
+        let received = Self::pull_assets(e, from, from, credited);
+        if let Some(payout) = data.payout.as_ref().filter(|payout| *payout != from) {
+            if profit_share > 0 {
+                token::Client::new(e, &Vault::query_asset(e)).transfer(from, payout, &profit_share);
+            }
+        }
+        let previous_impact = data.net_impact;
+        data.total_received += received;
+        data.net_impact = data.total_received - data.total_sent;
+        let gain = data.net_impact.max(0) - previous_impact.max(0);
+        storage::set_strategy_data(e, strategy, &data);
+        storage::set_last_report(e, strategy, e.ledger().timestamp());
+        Self::record_activity(e, strategy, StrategyAction::Deposit, amount, &data);
+
+        StrategyDeposit {
+            strategy: strategy.clone(),
+            amount,
+            profit_share,
+            total_received: data.total_received,
+        }
+        .publish(e);
+        StrategyReport {
+            strategy: strategy.clone(),
+            gain,
+            loss: 0,
+            fees: profit_share,
+            debt: (-data.net_impact).max(0),
+        }
+        .publish(e);
+    }
+
+    /// Transfers `amount` from `from` into the vault without minting shares, raising the
+    /// share price for every holder. Before the first deposit a donation would only
+    /// re-price the first depositor's shares, so it is rejected, as it is once the vault
+    /// is winding down or if it moves the share price past the circuit breaker.
+    pub fn donate(e: &Env, from: &Address, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(e, StrategyVaultError::InvalidAmount);
+        }
+        Self::require_active(e);
+        if Self::is_first_deposit(e) {
+            panic_with_error!(e, StrategyVaultError::VaultEmpty);
+        }
+        Self::check_price_move(e, from, amount);
+        let token_client = token::Client::new(e, &Vault::query_asset(e));
+        token_client.transfer(from, e.current_contract_address(), &amount);
+        Donation {
+            from: from.clone(),
+            amount,
+        }
+        .publish(e);
+    }
+
+    /// Deposits `assets` that `owner` just redeemed into `new_vault` for the owner,
+    /// returning the shares minted there
+    pub fn migrate_position(
+        e: &Env,
+        new_vault: &Address,
+        owner: &Address,
+        shares: i128,
+        assets: i128,
+    ) -> i128 {
+        let target = TargetVaultClient::new(e, new_vault);
+        if target.query_asset() != Vault::query_asset(e) {
+            panic_with_error!(e, StrategyVaultError::AssetMismatch);
+        }
+        let new_shares = target.deposit(&assets, owner, owner, owner);
+        PositionMigrated {
+            owner: owner.clone(),
+            new_vault: new_vault.clone(),
+            shares,
+            assets,
+            new_shares,
+        }
+        .publish(e);
+        new_shares
+    }
+
+    /// Sets the share of returned profit a strategy keeps
+    pub fn set_profit_share(e: &Env, strategy: &Address, rate: i128) {
+        Self::require_strategy(e, strategy);
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.profit_share = Rate::new(e, rate);
+        storage::set_strategy_data(e, strategy, &data);
+        ProfitShareSet {
+            strategy: strategy.clone(),
+            rate,
+        }
+        .publish(e);
+    }
+
+    /// Sets the one-time fee withheld from a strategy's withdrawals
+    pub fn set_borrow_fee(e: &Env, strategy: &Address, rate: i128) {
+        Self::require_strategy(e, strategy);
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.borrow_fee = Rate::new_fee(e, rate);
+        storage::set_strategy_data(e, strategy, &data);
+        BorrowFeeSet {
+            strategy: strategy.clone(),
+            rate,
+        }
+        .publish(e);
+    }
+
+    /// Sets or clears the manager allowed to act for a strategy
+    pub fn set_manager(e: &Env, strategy: &Address, manager: Option<Address>) {
+        Self::require_strategy(e, strategy);
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.manager = manager.clone();
+        storage::set_strategy_data(e, strategy, &data);
+        StrategyManagerSet {
+            strategy: strategy.clone(),
+            manager,
+        }
+        .publish(e);
+    }
+
+    /// Sets or clears the address a strategy's profit share is paid to
+    pub fn set_payout(e: &Env, strategy: &Address, payout: Option<Address>) {
+        Self::require_strategy(e, strategy);
+        let mut data = storage::get_strategy_data(e, strategy);
+        data.payout = payout.clone();
+        storage::set_strategy_data(e, strategy, &data);
+        StrategyPayoutSet {
+            strategy: strategy.clone(),
+            payout,
+        }
+        .publish(e);
+    }
+
+    /// Replaces the vault's display metadata
+    pub fn set_metadata(e: &Env, metadata: &VaultMetadata) {
+        storage::set_metadata(e, metadata);
+        MetadataUpdated {
+            metadata: metadata.clone(),
+        }
+        .publish(e);
+    }
+
+    /// Opts a receiver in or out of deposit/redeem hooks
+    pub fn set_receiver_hook(e: &Env, receiver: &Address, enabled: bool) {
+        storage::set_receiver_hook(e, receiver, enabled);
+        ReceiverHookSet {
+            receiver: receiver.clone(),
+            enabled,
+        }
+        .publish(e);
+    }
+
+    /// Notifies an opted-in receiver that it was credited shares
+    pub fn notify_deposit(e: &Env, receiver: &Address, from: &Address, assets: i128, shares: i128) {
+        if storage::get_receiver_hook(e, receiver) {
+            VaultReceiverClient::new(e, receiver).on_vault_deposit(
+                &e.current_contract_address(),
+                from,
+                &assets,
+                &shares,
+            );
+        }
+    }
+
+    /// Notifies an opted-in receiver that it was sent redeemed assets
+    pub fn notify_redeem(e: &Env, receiver: &Address, owner: &Address, assets: i128, shares: i128) {
+        if storage::get_receiver_hook(e, receiver) {
+            VaultReceiverClient::new(e, receiver).on_vault_redeem(
+                &e.current_contract_address(),
+                owner,
+                &assets,
+                &shares,
+            );
+        }
+    }
 }
@@ -1,15 +1,173 @@
 #![cfg(test)]
 
+extern crate std;
+
+use ed25519_dalek::{Signer, SigningKey};
+use proptest::prelude::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    token::StellarAssetClient,
-    Address, Env, String, Vec,
+    contract, contractimpl, symbol_short,
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    xdr::ToXdr,
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, Asset, ContractExecutable, ContractIdPreimage,
+        CreateContractArgs, HostFunction, Int128Parts, LedgerEntry, LedgerEntryData,
+        LedgerEntryExt, LedgerKey, LedgerKeyAccount, PublicKey, ScAddress, ScErrorCode,
+        ScErrorType, ScMapEntry, ScVal, SequenceNumber, Thresholds, Uint256,
+    },
+    Address, BytesN, Env, String, TryFromVal, Vec,
+};
+use std::rc::Rc;
+
+use crate::{
+    client::{self, VaultEvent},
+    merkle,
+    storage::{
+        AccountingSnapshot, AssetInfo, CouncilConfig, DepositCap, DepositCoupon, ExitFee,
+        IdleDeploy, LaunchCap, LaunchConfig, LaunchSeed, LockTier, RedemptionRecord, Rounding,
+        SeedDeposit, StrategyAction, StrategyActivity, StrategyStorageKey, UserPnl, VaultMetadata,
+        VaultStats, WithdrawLimit, RECENT_ACTIVITY_LEN, REDEMPTION_HISTORY_LEN,
+    },
+    strategy::{Rate, StrategyVaultError, BOOTSTRAP_SHARES, ROUNDING},
+    StrategyVaultContract, StrategyVaultContractClient,
 };
 
-use crate::{StrategyVaultContract, StrategyVaultContractClient};
+/// Receiver that records the last hook invocation as (assets, shares)
+#[contract]
+struct MockReceiver;
+
+#[contractimpl]
+impl MockReceiver {
+    pub fn on_vault_deposit(e: Env, _vault: Address, _from: Address, assets: i128, shares: i128) {
+        e.storage()
+            .instance()
+            .set(&symbol_short!("deposit"), &(assets, shares));
+    }
+
+    pub fn on_vault_redeem(e: Env, _vault: Address, _owner: Address, assets: i128, shares: i128) {
+        e.storage()
+            .instance()
+            .set(&symbol_short!("redeem"), &(assets, shares));
+    }
+
+    pub fn last(e: Env, kind: soroban_sdk::Symbol) -> Option<(i128, i128)> {
+        e.storage().instance().get(&kind)
+    }
+}
+
+/// Receiver that tries to re-enter the vault from its deposit hook
+#[contract]
+struct ReentrantReceiver;
+
+#[contractimpl]
+impl ReentrantReceiver {
+    pub fn on_vault_deposit(e: Env, vault: Address, from: Address, assets: i128, _shares: i128) {
+        let me = e.current_contract_address();
+        StrategyVaultContractClient::new(&e, &vault).deposit(&assets, &me, &from, &from);
+    }
+
+    pub fn on_vault_redeem(
+        _e: Env,
+        _vault: Address,
+        _owner: Address,
+        _assets: i128,
+        _shares: i128,
+    ) {
+    }
+}
+
+/// Underlying token that burns 1% of every transfer
+#[contract]
+struct FeeToken;
+
+#[contractimpl]
+impl FeeToken {
+    pub fn mint(e: Env, to: Address, amount: i128) {
+        let balance = Self::balance(e.clone(), to.clone());
+        e.storage().persistent().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(e: Env, id: Address) -> i128 {
+        e.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn decimals(_e: Env) -> u32 {
+        7
+    }
+
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::move_balance(&e, from, to, amount);
+    }
+
+    pub fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::move_balance(&e, from, to, amount);
+    }
+
+    fn move_balance(e: &Env, from: Address, to: Address, amount: i128) {
+        let from_balance = Self::balance(e.clone(), from.clone());
+        e.storage()
+            .persistent()
+            .set(&from, &(from_balance - amount));
+        Self::mint(e.clone(), to, amount - amount / 100);
+    }
+}
+
+/// Underlying token that tries to re-enter the vault from `transfer`
+#[contract]
+struct ReentrantToken;
+
+#[contractimpl]
+impl ReentrantToken {
+    pub fn set_vault(e: Env, vault: Address) {
+        e.storage().instance().set(&symbol_short!("vault"), &vault);
+    }
+
+    pub fn balance(_e: Env, _id: Address) -> i128 {
+        i128::MAX / 1_000_000
+    }
+
+    pub fn decimals(_e: Env) -> u32 {
+        7
+    }
+
+    pub fn transfer(e: Env, from: Address, _to: Address, amount: i128) {
+        let vault: Address = e.storage().instance().get(&symbol_short!("vault")).unwrap();
+        StrategyVaultContractClient::new(&e, &vault).deposit(&amount, &from, &from, &from);
+    }
+}
 
 const SCALAR_7: i128 = 10_000_000;
 const LOCK_TIME: u64 = 300;
+const MIN_INITIAL_DEPOSIT: i128 = SCALAR_7;
+const STRATEGY_DELAY: u64 = 86_400;
+
+fn register_vault(
+    env: &Env,
+    admin: &Address,
+    asset: &Address,
+    strategies: Vec<Address>,
+) -> Address {
+    env.register(
+        StrategyVaultContract,
+        (
+            admin.clone(),
+            String::from_str(env, "Vault Shares"),
+            String::from_str(env, "vTKN"),
+            asset.clone(),
+            0u32,
+            strategies,
+            LOCK_TIME,
+            MIN_INITIAL_DEPOSIT,
+            STRATEGY_DELAY,
+            LaunchConfig {
+                seed: LaunchSeed::None,
+                deposit_cap: LaunchCap::None,
+            },
+        ),
+    )
+}
 
 fn setup_test<'a>() -> (
     Env,
@@ -31,22 +189,29 @@ fn setup_test<'a>() -> (
 
     // Deploy vault
     let strategies = Vec::from_array(&env, [strategy.clone()]);
-    let vault_address = env.register(
-        StrategyVaultContract,
-        (
-            String::from_str(&env, "Vault Shares"),
-            String::from_str(&env, "vTKN"),
-            token.address(),
-            0u32,
-            strategies,
-            LOCK_TIME,
-        ),
-    );
+    let vault_address = register_vault(&env, &admin, &token.address(), strategies);
 
     let vault = StrategyVaultContractClient::new(&env, &vault_address);
+    vault.set_exposure_cap(&strategy, &None);
     (env, vault, token.address(), user, strategy)
 }
 
+/// Decodes every event the vault emitted during the last invocation
+fn decoded_events(env: &Env, vault: &Address) -> std::vec::Vec<VaultEvent> {
+    env.events()
+        .all()
+        .iter()
+        .filter(|(contract, _, _)| contract == vault)
+        .filter_map(|(_, topics, data)| {
+            let topics: std::vec::Vec<ScVal> = topics
+                .iter()
+                .map(|topic| ScVal::try_from_val(env, &topic).unwrap())
+                .collect();
+            client::decode(&topics, &ScVal::try_from_val(env, &data).unwrap()).ok()
+        })
+        .collect()
+}
+
 // ==================== Lock Mechanism Tests ====================
 
 #[test]
@@ -60,6 +225,33 @@ fn test_deposit_sets_lock() {
     assert!(vault.max_redeem(&user) > 0);
 }
 
+#[test]
+fn test_same_ledger_guard_blocks_exit_until_next_ledger() {
+    let (env, vault, _, user, _) = setup_test();
+    let recipient = Address::generate(&env);
+
+    vault.set_same_ledger_guard(&true);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::SameLedgerGuardSet(
+            client::SameLedgerGuardSetEvent { enabled: true }
+        )]
+    );
+    vault.set_lock_exempt(&user, &true);
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.max_redeem(&user), 0);
+    let blocked = Some(Ok(StrategyVaultError::SameLedgerExit.into()));
+    assert_eq!(vault.try_redeem(&1, &user, &user, &user).err(), blocked);
+    assert_eq!(vault.try_withdraw(&1, &user, &user, &user).err(), blocked);
+    assert_eq!(vault.try_transfer(&user, &recipient, &1).err(), blocked);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 5);
+    assert_eq!(vault.max_redeem(&user), shares);
+    vault.transfer(&user, &recipient, &1);
+    vault.redeem(&(shares - 1), &user, &user, &user);
+}
+
 #[test]
 fn test_mint_sets_lock() {
     let (_env, vault, _, user, _) = setup_test();
@@ -69,6 +261,98 @@ fn test_mint_sets_lock() {
     assert!(vault.lock_duration(&user) > 0);
 }
 
+#[test]
+fn test_lock_tiers_lengthen_lock_for_large_positions() {
+    let (env, vault, token, user, _) = setup_test();
+    let whale = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&whale, &(100_000 * SCALAR_7));
+
+    vault.set_lock_tiers(&Vec::from_array(
+        &env,
+        [
+            LockTier {
+                min_assets: 5000 * SCALAR_7,
+                lock_time: 3600,
+            },
+            LockTier {
+                min_assets: 50_000 * SCALAR_7,
+                lock_time: 86_400,
+            },
+        ],
+    ));
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.deposit(&(10_000 * SCALAR_7), &whale, &whale, &whale);
+    assert_eq!(vault.lock_duration(&user), LOCK_TIME);
+    assert_eq!(vault.lock_duration(&whale), 3600);
+
+    // The tier follows the whole position, not the size of the latest deposit
+    vault.deposit(&(40_000 * SCALAR_7), &whale, &whale, &whale);
+    assert_eq!(vault.lock_duration(&whale), 86_400);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #424)")] // InvalidConfiguration
+fn test_unsorted_lock_tiers_fail() {
+    let (env, vault, _, _, _) = setup_test();
+
+    vault.set_lock_tiers(&Vec::from_array(
+        &env,
+        [
+            LockTier {
+                min_assets: 50_000 * SCALAR_7,
+                lock_time: 86_400,
+            },
+            LockTier {
+                min_assets: 5000 * SCALAR_7,
+                lock_time: 3600,
+            },
+        ],
+    ));
+}
+
+#[test]
+fn test_lock_exempt_account_skips_lock_and_exit_fee() {
+    let (env, vault, _, user, _) = setup_test();
+    let recipient = Address::generate(&env);
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    vault.set_lock_exempt(&user, &true);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::LockExemptionSet(
+            client::LockExemptionSetEvent {
+                account: ScAddress::from(&user),
+                exempt: true,
+            }
+        )]
+    );
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    assert!(!vault.is_locked(&user));
+    assert_eq!(vault.exit_fee_rate(&user), 0);
+    vault.transfer(&user, &recipient, &(shares / 2));
+
+    vault.set_lock_exempt(&user, &false);
+    assert!(!vault.is_lock_exempt(&user));
+    assert!(vault.is_locked(&user));
+}
+
+#[test]
+fn test_is_locked_until_lock_expires() {
+    let (env, vault, _, user, _) = setup_test();
+    assert!(!vault.is_locked(&user));
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    assert!(vault.is_locked(&user));
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME);
+    assert!(!vault.is_locked(&user));
+}
+
 #[test]
 fn test_max_withdraw_returns_value_when_locked() {
     let (_env, vault, _, user, _) = setup_test();
@@ -79,6 +363,16 @@ fn test_max_withdraw_returns_value_when_locked() {
     assert!(vault.max_withdraw(&user) > 0);
 }
 
+#[test]
+fn test_version_returns_crate_version() {
+    let (env, vault, _, _, _) = setup_test();
+
+    assert_eq!(
+        vault.version(),
+        String::from_str(&env, env!("CARGO_PKG_VERSION"))
+    );
+}
+
 #[test]
 fn test_lock_time_returns_configured_value() {
     let (_env, vault, _, _, _) = setup_test();
@@ -236,6 +530,7 @@ fn test_strategy_withdraw_decreases_assets() {
 
     vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
     let initial_assets = vault.total_assets();
+    assert_eq!(vault.max_deployed_rate(), SCALAR_7);
 
     // Strategy withdraws
     vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
@@ -244,20 +539,3109 @@ fn test_strategy_withdraw_decreases_assets() {
 }
 
 #[test]
-#[should_panic(expected = "Error(Contract, #422)")] // UnauthorizedStrategy
-fn test_unauthorized_strategy_fails() {
-    let (env, vault, _, user, _) = setup_test();
-    let fake_strategy = Address::generate(&env);
+fn test_strategy_deposit_increases_assets() {
+    let (_env, vault, _token, user, strategy) = setup_test();
 
     vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
-    vault.strategy_withdraw(&fake_strategy, &(1000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(2000 * SCALAR_7));
+
+    assert_eq!(vault.total_assets(), 10_000 * SCALAR_7);
+    assert_eq!(vault.get_strategy(&strategy).net_impact, 0);
 }
 
 #[test]
-#[should_panic(expected = "Error(Contract, #420)")] // InvalidAmount
-fn test_zero_strategy_withdraw_fails() {
-    let (_, vault, _, user, strategy) = setup_test();
+fn test_recent_strategy_activity_keeps_latest_flows() {
+    let (env, vault, token, user, strategy) = setup_test();
 
     vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
-    vault.strategy_withdraw(&strategy, &0);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(500 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(500 * SCALAR_7));
+
+    let recent = vault.recent_strategy_activity();
+    assert_eq!(recent.len(), 2);
+    assert_eq!(
+        recent.get(1),
+        Some(StrategyActivity {
+            strategy: strategy.clone(),
+            action: StrategyAction::Deposit,
+            amount: 500 * SCALAR_7,
+            net_impact: -1500 * SCALAR_7,
+            timestamp: env.ledger().timestamp(),
+        })
+    );
+
+    for _ in 0..RECENT_ACTIVITY_LEN {
+        vault.strategy_withdraw(&strategy, &SCALAR_7);
+    }
+    let recent = vault.recent_strategy_activity();
+    assert_eq!(recent.len(), RECENT_ACTIVITY_LEN);
+    assert!(
+        recent
+            .iter()
+            .all(|activity| activity.action == StrategyAction::Withdraw
+                && activity.amount == SCALAR_7)
+    );
+}
+
+#[test]
+fn test_strategy_net_impact_tracks_flows() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    assert_eq!(vault.get_strategy(&strategy).net_impact, -2000 * SCALAR_7);
+
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(500 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(2500 * SCALAR_7));
+
+    let data = vault.get_strategy(&strategy);
+    assert_eq!(data.net_impact, 500 * SCALAR_7);
+    assert_eq!(data.total_sent, 2000 * SCALAR_7);
+    assert_eq!(data.total_received, 2500 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 10_500 * SCALAR_7);
+}
+
+#[test]
+fn test_strategy_keeps_profit_share() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 5)); // 20%
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(500 * SCALAR_7));
+
+    // Returning principal + 500 profit: 100 (20%) stays with the strategy
+    vault.strategy_deposit(&strategy, &(2500 * SCALAR_7));
+
+    assert_eq!(vault.total_assets(), 10_400 * SCALAR_7);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&strategy),
+        100 * SCALAR_7
+    );
+    let data = vault.get_strategy(&strategy);
+    assert_eq!(data.net_impact, 400 * SCALAR_7);
+    assert_eq!(data.profit_share.value(), SCALAR_7 / 5);
+}
+
+#[test]
+fn test_profit_share_sent_to_strategy_payout() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let payout = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 5)); // 20%
+    vault.set_strategy_payout(&strategy, &Some(payout.clone()));
+    assert_eq!(env.auths()[0].0, strategy);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(500 * SCALAR_7));
+
+    vault.strategy_deposit(&strategy, &(2500 * SCALAR_7));
+
+    let token_client = TokenClient::new(&env, &token);
+    assert_eq!(vault.total_assets(), 10_400 * SCALAR_7);
+    assert_eq!(token_client.balance(&payout), 100 * SCALAR_7);
+    assert_eq!(token_client.balance(&strategy), 0);
+    assert_eq!(vault.get_strategy(&strategy).payout, Some(payout));
+}
+
+#[test]
+fn test_borrow_fee_is_withheld_for_depositors() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_borrow_fee(&strategy, &(SCALAR_7 / 100)); // 1%
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7));
+
+    assert_eq!(
+        decoded_events(&env, &vault.address).last().cloned(),
+        Some(VaultEvent::BorrowFeeCharged(
+            client::BorrowFeeChargedEvent {
+                strategy: ScAddress::from(&strategy),
+                fee: 10 * SCALAR_7,
+            }
+        ))
+    );
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&strategy),
+        990 * SCALAR_7
+    );
+    assert_eq!(vault.total_assets(), 9010 * SCALAR_7);
+    assert_eq!(vault.get_strategy(&strategy).net_impact, -1000 * SCALAR_7);
+    assert_eq!(
+        vault.try_set_borrow_fee(&strategy, &SCALAR_7).err(),
+        Some(Ok(StrategyVaultError::InvalidConfiguration.into()))
+    );
+}
+
+#[test]
+fn test_profit_share_not_charged_on_principal() {
+    let (_env, vault, _token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 5));
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(1500 * SCALAR_7));
+
+    assert_eq!(vault.get_strategy(&strategy).net_impact, -500 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 9500 * SCALAR_7);
+}
+
+#[test]
+fn test_manager_withdraws_and_deposits_for_strategy() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let manager = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_strategy_manager(&strategy, &Some(manager.clone()));
+
+    vault.manager_withdraw(&manager, &strategy, &(2000 * SCALAR_7));
+    assert_eq!(env.auths()[0].0, manager);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&strategy),
+        2000 * SCALAR_7
+    );
+
+    StellarAssetClient::new(&env, &token).mint(&manager, &(2000 * SCALAR_7));
+    vault.manager_deposit(&manager, &strategy, &(2000 * SCALAR_7));
+
+    assert_eq!(vault.total_assets(), 10_000 * SCALAR_7);
+    assert_eq!(vault.get_strategy(&strategy).net_impact, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #425)")] // UnauthorizedManager
+fn test_non_manager_withdraw_fails() {
+    let (env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.manager_withdraw(&Address::generate(&env), &strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #425)")] // UnauthorizedManager
+fn test_cleared_manager_withdraw_fails() {
+    let (env, vault, _, user, strategy) = setup_test();
+    let manager = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_strategy_manager(&strategy, &Some(manager.clone()));
+    vault.set_strategy_manager(&strategy, &None);
+    vault.manager_withdraw(&manager, &strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+fn test_deployed_assets_sums_strategy_exposure() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(3000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(1000 * SCALAR_7));
+
+    assert_eq!(vault.deployed_assets(), 2000 * SCALAR_7);
+}
+
+#[test]
+fn test_withdraw_up_to_deployment_cap_succeeds() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_deployed_rate(&(SCALAR_7 / 2)); // 50%
+    vault.strategy_withdraw(&strategy, &(3000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+
+    assert_eq!(vault.deployed_assets(), 5000 * SCALAR_7);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #426)")] // DeploymentCapExceeded
+fn test_withdraw_above_deployment_cap_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_deployed_rate(&(SCALAR_7 / 2)); // 50%
+    vault.strategy_withdraw(&strategy, &(3000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7 + 1));
+}
+
+#[test]
+fn test_strategy_withdraw_within_price_change_limit_succeeds() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_price_change(&(SCALAR_7 / 10)); // 10%
+    vault.strategy_withdraw(&strategy, &(999 * SCALAR_7));
+
+    assert_eq!(vault.total_assets(), 9001 * SCALAR_7);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #428)")] // PriceDeviationExceeded
+fn test_strategy_withdraw_above_price_change_limit_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_price_change(&(SCALAR_7 / 10)); // 10%
+    vault.strategy_withdraw(&strategy, &(1001 * SCALAR_7));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #428)")] // PriceDeviationExceeded
+fn test_strategy_deposit_above_price_change_limit_fails() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_price_change(&(SCALAR_7 / 10)); // 10%
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(2000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(2000 * SCALAR_7));
+}
+
+#[test]
+fn test_price_override_allows_one_large_operation() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_price_change(&(SCALAR_7 / 10)); // 10%
+    vault.grant_price_override(&strategy);
+    vault.strategy_withdraw(&strategy, &(3000 * SCALAR_7));
+
+    // Override is consumed
+    assert!(vault
+        .try_strategy_withdraw(&strategy, &(2000 * SCALAR_7))
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #432)")] // StrategyExposureCapExceeded
+fn test_withdraw_above_strategy_exposure_cap_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_exposure_cap(&strategy, &Some(1000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(600 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(400 * SCALAR_7 + 1));
+}
+
+#[test]
+fn test_council_approves_large_strategy_withdrawal() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let council = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_council(&Some(CouncilConfig {
+        council: council.clone(),
+        threshold: 1000 * SCALAR_7,
+        window: 3600,
+    }));
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7));
+    assert_eq!(
+        vault
+            .try_strategy_withdraw(&strategy, &(1000 * SCALAR_7 + 1))
+            .err(),
+        Some(Ok(StrategyVaultError::CouncilApprovalRequired.into()))
+    );
+
+    vault.request_withdraw(&strategy, &(3000 * SCALAR_7));
+    let expires_at = env.ledger().timestamp() + 3600;
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::StrategyWithdrawRequested(
+            client::StrategyWithdrawRequestedEvent {
+                strategy: ScAddress::from(&strategy),
+                amount: 3000 * SCALAR_7,
+                expires_at,
+            }
+        )]
+    );
+
+    vault.approve_withdraw(&strategy);
+    assert_eq!(env.auths()[0].0, council);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&strategy),
+        4000 * SCALAR_7
+    );
+    assert_eq!(vault.withdraw_request(&strategy), None);
+}
+
+#[test]
+fn test_council_threshold_sums_withdrawals_per_window() {
+    let (env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_council(&Some(CouncilConfig {
+        council: Address::generate(&env),
+        threshold: 1000 * SCALAR_7,
+        window: 3600,
+    }));
+    vault.strategy_withdraw(&strategy, &(600 * SCALAR_7));
+    assert_eq!(vault.council_budget(&strategy), 400 * SCALAR_7);
+
+    // Two withdrawals under the threshold cannot add up to more than it
+    assert_eq!(
+        vault
+            .try_strategy_withdraw(&strategy, &(600 * SCALAR_7))
+            .err(),
+        Some(Ok(StrategyVaultError::CouncilApprovalRequired.into()))
+    );
+    vault.strategy_withdraw(&strategy, &(400 * SCALAR_7));
+    assert_eq!(vault.council_budget(&strategy), 0);
+
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+    assert_eq!(vault.council_budget(&strategy), 1000 * SCALAR_7);
+    vault.strategy_withdraw(&strategy, &(600 * SCALAR_7));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #445)")] // WithdrawRequestNotFound
+fn test_council_cannot_approve_expired_request() {
+    let (env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_council(&Some(CouncilConfig {
+        council: Address::generate(&env),
+        threshold: 1000 * SCALAR_7,
+        window: 3600,
+    }));
+    vault.request_withdraw(&strategy, &(3000 * SCALAR_7));
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    vault.approve_withdraw(&strategy);
+}
+
+#[test]
+fn test_stale_strategy_reports_pause_deposits() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_report_interval(&3600);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    assert!(vault.is_degraded());
+    assert_eq!(vault.max_deposit(&user), 0);
+    assert_eq!(
+        vault.try_deposit(&SCALAR_7, &user, &user, &user).err(),
+        Some(Ok(StrategyVaultError::DepositsPaused.into()))
+    );
+    // Redemptions stay open at the cash-based price
+    vault.redeem(&SCALAR_7, &user, &user, &user);
+
+    vault.strategy_heartbeat(&strategy);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::StrategyHeartbeat(
+            client::StrategyHeartbeatEvent {
+                strategy: ScAddress::from(&strategy),
+            }
+        )]
+    );
+    assert!(!vault.is_degraded());
+    vault.deposit(&SCALAR_7, &user, &user, &user);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    assert!(vault.is_degraded());
+    StellarAssetClient::new(&env, &token).mint(&strategy, &SCALAR_7);
+    vault.strategy_deposit(&strategy, &SCALAR_7);
+    assert!(!vault.is_degraded());
+}
+
+#[test]
+fn test_each_strategy_with_deployed_assets_must_report() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let other = Address::generate(&env);
+    vault.add_strategy(&other);
+    env.ledger().with_mut(|li| li.timestamp += STRATEGY_DELAY);
+    vault.activate_strategy(&other);
+    vault.set_exposure_cap(&other, &None);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_report_interval(&3600);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    vault.strategy_withdraw(&other, &(2000 * SCALAR_7));
+
+    // One strategy's heartbeat does not cover the other's stale position
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    vault.strategy_heartbeat(&strategy);
+    assert!(vault.is_degraded());
+    vault.strategy_heartbeat(&other);
+    assert!(!vault.is_degraded());
+
+    // A strategy without deployed assets never degrades the vault
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    StellarAssetClient::new(&env, &token).mint(&other, &(2000 * SCALAR_7));
+    vault.strategy_deposit(&other, &(2000 * SCALAR_7));
+    vault.strategy_heartbeat(&strategy);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    vault.strategy_heartbeat(&strategy);
+    assert!(!vault.is_degraded());
+}
+
+#[test]
+fn test_manager_sends_strategy_heartbeat() {
+    let (env, vault, _, user, strategy) = setup_test();
+    let manager = Address::generate(&env);
+    vault.set_strategy_manager(&strategy, &Some(manager.clone()));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_report_interval(&3600);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3601);
+    assert!(vault.is_degraded());
+
+    assert_eq!(
+        vault
+            .try_manager_heartbeat(&Address::generate(&env), &strategy)
+            .err(),
+        Some(Ok(StrategyVaultError::UnauthorizedManager.into()))
+    );
+    vault.manager_heartbeat(&manager, &strategy);
+    assert_eq!(env.auths()[0].0, manager);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::StrategyHeartbeat(
+            client::StrategyHeartbeatEvent {
+                strategy: ScAddress::from(&strategy),
+            }
+        )]
+    );
+    assert!(!vault.is_degraded());
+}
+
+#[test]
+fn test_deploy_idle_pushes_surplus_to_default_strategy() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_idle_deploy(&Some(IdleDeploy {
+        strategy: strategy.clone(),
+        threshold: 2000 * SCALAR_7,
+    }));
+
+    assert_eq!(vault.deploy_idle(), 8000 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 2000 * SCALAR_7);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&strategy),
+        8000 * SCALAR_7
+    );
+    assert_eq!(
+        vault.try_deploy_idle().err(),
+        Some(Ok(StrategyVaultError::InvalidAmount.into()))
+    );
+
+    // With a council, pushes share the strategy's council budget for the window
+    vault.deposit(&(5000 * SCALAR_7), &user, &user, &user);
+    vault.set_council(&Some(CouncilConfig {
+        council: Address::generate(&env),
+        threshold: 1000 * SCALAR_7,
+        window: 3600,
+    }));
+    vault.strategy_withdraw(&strategy, &(400 * SCALAR_7));
+    assert_eq!(vault.deploy_idle(), 600 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 6000 * SCALAR_7);
+    assert_eq!(
+        vault.try_deploy_idle().err(),
+        Some(Ok(StrategyVaultError::CouncilApprovalRequired.into()))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+    assert_eq!(vault.deploy_idle(), 1000 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 5000 * SCALAR_7);
+}
+
+#[test]
+fn test_strategy_exposure_cap_counts_returns() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_exposure_cap(&strategy, &Some(1000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(500 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(500 * SCALAR_7));
+
+    assert_eq!(
+        vault.get_strategy(&strategy).exposure_cap,
+        Some(1000 * SCALAR_7)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #426)")] // DeploymentCapExceeded
+fn test_withdraw_above_absolute_deployment_cap_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_max_deployed(&Some(1000 * SCALAR_7));
+    assert_eq!(vault.max_deployed(), Some(1000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7 + 1));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #424)")] // InvalidConfiguration
+fn test_profit_share_above_100_percent_fails() {
+    let (_env, vault, _, _, strategy) = setup_test();
+
+    vault.set_profit_share(&strategy, &(SCALAR_7 + 1));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #422)")] // UnauthorizedStrategy
+fn test_unauthorized_strategy_deposit_fails() {
+    let (env, vault, _, user, _) = setup_test();
+    let fake_strategy = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_deposit(&fake_strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+fn test_added_strategy_activates_after_delay() {
+    let (env, vault, _, user, _) = setup_test();
+    let new_strategy = Address::generate(&env);
+    let activation_time = env.ledger().timestamp() + STRATEGY_DELAY;
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.add_strategy(&new_strategy);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::StrategyScheduled(
+            client::StrategyScheduledEvent {
+                strategy: ScAddress::from(&new_strategy),
+                activation_time,
+            }
+        )]
+    );
+    assert_eq!(
+        vault.pending_strategies().get(new_strategy.clone()),
+        Some(activation_time)
+    );
+    assert!(!vault.strategies().contains(&new_strategy));
+
+    env.ledger().set_timestamp(activation_time);
+    vault.activate_strategy(&new_strategy);
+
+    assert!(vault.strategies().contains(&new_strategy));
+    assert!(vault.pending_strategies().is_empty());
+    vault.set_exposure_cap(&new_strategy, &None);
+    vault.strategy_withdraw(&new_strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+fn test_activated_strategy_withdraws_only_its_contributions() {
+    let (env, vault, token, user, _) = setup_test();
+    let new_strategy = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.add_strategy(&new_strategy);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + STRATEGY_DELAY);
+    vault.activate_strategy(&new_strategy);
+
+    assert_eq!(vault.get_strategy(&new_strategy).exposure_cap, Some(0));
+    assert_eq!(
+        vault.try_strategy_withdraw(&new_strategy, &1).err(),
+        Some(Ok(StrategyVaultError::StrategyExposureCapExceeded.into()))
+    );
+
+    StellarAssetClient::new(&env, &token).mint(&new_strategy, &(500 * SCALAR_7));
+    vault.strategy_deposit(&new_strategy, &(500 * SCALAR_7));
+    vault.strategy_withdraw(&new_strategy, &(500 * SCALAR_7));
+    assert_eq!(vault.get_strategy(&new_strategy).net_impact, 0);
+    assert!(vault.try_strategy_withdraw(&new_strategy, &1).is_err());
+
+    vault.set_exposure_cap(&new_strategy, &None);
+    vault.strategy_withdraw(&new_strategy, &(1000 * SCALAR_7));
+    assert_eq!(vault.deployed_assets(), 1000 * SCALAR_7);
+}
+
+#[test]
+fn test_constructor_strategy_starts_with_zero_exposure_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let strategy = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    StellarAssetClient::new(&env, &token).mint(&user, &(10_000 * SCALAR_7));
+    let vault = StrategyVaultContractClient::new(
+        &env,
+        &register_vault(
+            &env,
+            &admin,
+            &token,
+            Vec::from_array(&env, [strategy.clone()]),
+        ),
+    );
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.get_strategy(&strategy).exposure_cap, Some(0));
+    assert_eq!(
+        vault.try_strategy_withdraw(&strategy, &1).err(),
+        Some(Ok(StrategyVaultError::StrategyExposureCapExceeded.into()))
+    );
+
+    vault.set_exposure_cap(&strategy, &Some(1000 * SCALAR_7));
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7));
+    assert_eq!(vault.deployed_assets(), 1000 * SCALAR_7);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #431)")] // StrategyTimelocked
+fn test_activate_strategy_before_delay_fails() {
+    let (env, vault, _, _, _) = setup_test();
+    let new_strategy = Address::generate(&env);
+
+    vault.add_strategy(&new_strategy);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + STRATEGY_DELAY - 1);
+    vault.activate_strategy(&new_strategy);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #422)")] // UnauthorizedStrategy
+fn test_pending_strategy_cannot_withdraw() {
+    let (env, vault, _, user, _) = setup_test();
+    let new_strategy = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.add_strategy(&new_strategy);
+    vault.strategy_withdraw(&new_strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #429)")] // StrategyAlreadyAdded
+fn test_add_existing_strategy_fails() {
+    let (_env, vault, _, _, strategy) = setup_test();
+
+    vault.add_strategy(&strategy);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #430)")] // StrategyNotPending
+fn test_activate_unscheduled_strategy_fails() {
+    let (env, vault, _, _, _) = setup_test();
+
+    vault.activate_strategy(&Address::generate(&env));
+}
+
+#[test]
+fn test_migrate_strategy_moves_accounting() {
+    let (env, vault, _, user, strategy) = setup_test();
+    let new_strategy = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 10));
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    vault.set_council(&Some(CouncilConfig {
+        council: Address::generate(&env),
+        threshold: 1000 * SCALAR_7,
+        window: 2 * STRATEGY_DELAY,
+    }));
+    vault.request_withdraw(&strategy, &(1500 * SCALAR_7));
+    vault.grant_price_override(&strategy);
+    vault.set_idle_deploy(&Some(IdleDeploy {
+        strategy: strategy.clone(),
+        threshold: 5000 * SCALAR_7,
+    }));
+    vault.add_strategy(&new_strategy);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + STRATEGY_DELAY);
+
+    vault.migrate_strategy(&strategy, &new_strategy);
+
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![
+            VaultEvent::StrategyMigrated(client::StrategyMigratedEvent {
+                old_strategy: ScAddress::from(&strategy),
+                new_strategy: ScAddress::from(&new_strategy),
+            }),
+            VaultEvent::IdleDeploySet(client::IdleDeploySetEvent {
+                strategy: Some(ScAddress::from(&new_strategy)),
+                threshold: 5000 * SCALAR_7,
+            }),
+        ]
+    );
+    assert_eq!(vault.withdraw_request(&strategy), None);
+    assert_eq!(
+        vault
+            .withdraw_request(&new_strategy)
+            .map(|request| request.amount),
+        Some(1500 * SCALAR_7)
+    );
+    let has_override = |strategy: &Address| {
+        env.as_contract(&vault.address, || {
+            env.storage()
+                .instance()
+                .has(&StrategyStorageKey::PriceOverride(strategy.clone()))
+        })
+    };
+    assert!(!has_override(&strategy));
+    assert!(has_override(&new_strategy));
+    assert_eq!(vault.idle_deploy().unwrap().strategy, new_strategy);
+    assert_eq!(
+        vault.strategies(),
+        Vec::from_array(&env, [new_strategy.clone()])
+    );
+    let data = vault.get_strategy(&new_strategy);
+    assert_eq!(data.net_impact, -2000 * SCALAR_7);
+    assert_eq!(data.profit_share.value(), SCALAR_7 / 10);
+    assert_eq!(vault.deployed_assets(), 2000 * SCALAR_7);
+    assert!(vault.try_get_strategy(&strategy).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #431)")] // StrategyTimelocked
+fn test_migrate_strategy_respects_timelock() {
+    let (env, vault, _, _, strategy) = setup_test();
+    let new_strategy = Address::generate(&env);
+
+    vault.add_strategy(&new_strategy);
+    vault.migrate_strategy(&strategy, &new_strategy);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #422)")] // UnauthorizedStrategy
+fn test_unauthorized_strategy_fails() {
+    let (env, vault, _, user, _) = setup_test();
+    let fake_strategy = Address::generate(&env);
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&fake_strategy, &(1000 * SCALAR_7));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #420)")] // InvalidAmount
+fn test_zero_strategy_withdraw_fails() {
+    let (_, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &0);
+}
+
+// ==================== Receiver Hook Tests ====================
+
+#[test]
+fn test_receiver_hook_disabled_by_default() {
+    let (env, vault, _, user, _) = setup_test();
+    let receiver = env.register(MockReceiver, ());
+
+    vault.deposit(&(1000 * SCALAR_7), &receiver, &user, &user);
+
+    assert!(!vault.receiver_hook(&receiver));
+    let client = MockReceiverClient::new(&env, &receiver);
+    assert_eq!(client.last(&symbol_short!("deposit")), None);
+}
+
+#[test]
+fn test_receiver_hook_called_on_deposit_and_redeem() {
+    let (env, vault, _, user, _) = setup_test();
+    let receiver = env.register(MockReceiver, ());
+    let client = MockReceiverClient::new(&env, &receiver);
+
+    vault.set_receiver_hook(&receiver, &true);
+    assert!(vault.receiver_hook(&receiver));
+
+    let shares = vault.deposit(&(1000 * SCALAR_7), &receiver, &user, &user);
+    assert_eq!(
+        client.last(&symbol_short!("deposit")),
+        Some((1000 * SCALAR_7, shares))
+    );
+
+    let assets = vault.redeem(&(400 * SCALAR_7), &receiver, &receiver, &receiver);
+    assert_eq!(
+        client.last(&symbol_short!("redeem")),
+        Some((assets, 400 * SCALAR_7))
+    );
+}
+
+#[test]
+fn test_receiver_hook_can_be_disabled() {
+    let (env, vault, _, user, _) = setup_test();
+    let receiver = env.register(MockReceiver, ());
+    let client = MockReceiverClient::new(&env, &receiver);
+
+    vault.set_receiver_hook(&receiver, &true);
+    vault.set_receiver_hook(&receiver, &false);
+    vault.mint(&(1000 * SCALAR_7), &receiver, &user, &user);
+
+    assert_eq!(client.last(&symbol_short!("deposit")), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Context, InvalidAction)")]
+fn test_receiver_hook_reentry_fails() {
+    let (env, vault, _, user, _) = setup_test();
+    let receiver = env.register(ReentrantReceiver, ());
+
+    vault.set_receiver_hook(&receiver, &true);
+    vault.deposit(&(1000 * SCALAR_7), &receiver, &user, &user);
+}
+
+#[test]
+fn test_underlying_token_cannot_reenter_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let token = ReentrantTokenClient::new(&env, &env.register(ReentrantToken, ()));
+    let vault_address = register_vault(
+        &env,
+        &Address::generate(&env),
+        &token.address,
+        Vec::new(&env),
+    );
+    let vault = StrategyVaultContractClient::new(&env, &vault_address);
+    token.set_vault(&vault_address);
+    let user = Address::generate(&env);
+
+    // The host aborts the whole deposit when the token calls back into the vault
+    assert_eq!(
+        vault.try_deposit(&(1000 * SCALAR_7), &user, &user, &user),
+        Err(Ok(soroban_sdk::Error::from_type_and_code(
+            ScErrorType::Context,
+            ScErrorCode::InvalidAction
+        )))
+    );
+    assert_eq!(vault.total_supply(), 0);
+    assert_eq!(vault.balance(&user), 0);
+}
+
+// ==================== Event Decoding Tests ====================
+
+#[test]
+fn test_decode_deposit_event() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    let events = decoded_events(&env, &vault.address);
+    assert!(events.contains(&VaultEvent::Deposit(client::DepositEvent {
+        operator: ScAddress::from(&user),
+        from: ScAddress::from(&user),
+        receiver: ScAddress::from(&user),
+        assets: 1000 * SCALAR_7,
+        shares,
+    })));
+}
+
+#[test]
+fn test_decode_withdraw_event() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    let assets = vault.redeem(&(400 * SCALAR_7), &user, &user, &user);
+
+    let events = decoded_events(&env, &vault.address);
+    assert!(
+        events.contains(&VaultEvent::Withdraw(client::WithdrawEvent {
+            operator: ScAddress::from(&user),
+            receiver: ScAddress::from(&user),
+            owner: ScAddress::from(&user),
+            assets,
+            shares: 400 * SCALAR_7,
+        }))
+    );
+}
+
+#[test]
+fn test_decode_strategy_withdraw_event() {
+    let (env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::StrategyWithdraw(
+            client::StrategyWithdrawEvent {
+                strategy: ScAddress::from(&strategy),
+                amount: 2000 * SCALAR_7,
+                total_sent: Some(2000 * SCALAR_7),
+            }
+        )]
+    );
+}
+
+#[test]
+fn test_strategy_deposit_emits_report() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 5)); // 20%
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(500 * SCALAR_7));
+    // A partial return realizes no loss: the unreturned 500 stays outstanding as debt
+    vault.strategy_deposit(&strategy, &(1500 * SCALAR_7));
+    assert_eq!(
+        decoded_events(&env, &vault.address).last(),
+        Some(&VaultEvent::StrategyReport(client::StrategyReportEvent {
+            strategy: ScAddress::from(&strategy),
+            gain: 0,
+            loss: 0,
+            fees: 0,
+            debt: 500 * SCALAR_7,
+        }))
+    );
+
+    // Returning the remaining 500 principal + 500 profit: 100 (20%) is kept as fees
+    vault.strategy_deposit(&strategy, &(1000 * SCALAR_7));
+    assert_eq!(
+        decoded_events(&env, &vault.address).last(),
+        Some(&VaultEvent::StrategyReport(client::StrategyReportEvent {
+            strategy: ScAddress::from(&strategy),
+            gain: 400 * SCALAR_7,
+            loss: 0,
+            fees: 100 * SCALAR_7,
+            debt: 0,
+        }))
+    );
+}
+
+#[test]
+fn test_decode_rejects_unknown_event() {
+    let topics = [ScVal::Symbol("unknown".try_into().unwrap())];
+
+    assert_eq!(
+        client::decode(&topics, &ScVal::Void),
+        Err(client::DecodeError::UnknownEvent)
+    );
+}
+
+#[test]
+fn test_strategy_withdraw_v2_topics_and_payload_are_stable() {
+    let (env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    let topics: std::vec::Vec<ScVal> = topics
+        .iter()
+        .map(|topic| ScVal::try_from_val(&env, &topic).unwrap())
+        .collect();
+    assert_eq!(
+        topics,
+        std::vec![
+            ScVal::Symbol("strategy_withdraw".try_into().unwrap()),
+            ScVal::Symbol("v2".try_into().unwrap()),
+            ScVal::Address(ScAddress::from(&strategy)),
+        ]
+    );
+    let data = ScVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(
+        client::decode_versioned(&topics, &data),
+        Ok((
+            2,
+            VaultEvent::StrategyWithdraw(client::StrategyWithdrawEvent {
+                strategy: ScAddress::from(&strategy),
+                amount: 2000 * SCALAR_7,
+                total_sent: Some(2000 * SCALAR_7),
+            })
+        ))
+    );
+}
+
+#[test]
+fn test_decode_unversioned_event_as_v0() {
+    let env = Env::default();
+    let strategy = Address::generate(&env);
+    let topics = [
+        ScVal::Symbol("strategy_withdraw".try_into().unwrap()),
+        ScVal::Address(ScAddress::from(&strategy)),
+    ];
+    let data = ScVal::Map(Some(
+        std::vec![ScMapEntry {
+            key: ScVal::Symbol("amount".try_into().unwrap()),
+            val: ScVal::I128(Int128Parts { hi: 0, lo: 42 }),
+        }]
+        .try_into()
+        .unwrap(),
+    ));
+
+    assert_eq!(
+        client::decode_versioned(&topics, &data),
+        Ok((
+            0,
+            VaultEvent::StrategyWithdraw(client::StrategyWithdrawEvent {
+                strategy: ScAddress::from(&strategy),
+                amount: 42,
+                total_sent: None,
+            })
+        ))
+    );
+}
+
+#[test]
+fn test_decode_v1_strategy_withdraw_without_totals() {
+    let env = Env::default();
+    let strategy = Address::generate(&env);
+    let topics = [
+        ScVal::Symbol("strategy_withdraw".try_into().unwrap()),
+        ScVal::Symbol("v1".try_into().unwrap()),
+        ScVal::Address(ScAddress::from(&strategy)),
+    ];
+    let data = ScVal::Map(Some(
+        std::vec![ScMapEntry {
+            key: ScVal::Symbol("amount".try_into().unwrap()),
+            val: ScVal::I128(Int128Parts { hi: 0, lo: 42 }),
+        }]
+        .try_into()
+        .unwrap(),
+    ));
+
+    assert_eq!(
+        client::decode_versioned(&topics, &data),
+        Ok((
+            1,
+            VaultEvent::StrategyWithdraw(client::StrategyWithdrawEvent {
+                strategy: ScAddress::from(&strategy),
+                amount: 42,
+                total_sent: None,
+            })
+        ))
+    );
+}
+
+#[test]
+fn test_decode_rejects_future_version() {
+    let env = Env::default();
+    let strategy = Address::generate(&env);
+    let topics = [
+        ScVal::Symbol("strategy_withdraw".try_into().unwrap()),
+        ScVal::Symbol("v99".try_into().unwrap()),
+        ScVal::Address(ScAddress::from(&strategy)),
+    ];
+
+    assert_eq!(
+        client::decode(&topics, &ScVal::Void),
+        Err(client::DecodeError::UnsupportedVersion)
+    );
+}
+
+// ==================== Bootstrap Tests ====================
+
+#[test]
+#[should_panic(expected = "Error(Contract, #424)")] // InvalidConfiguration
+fn test_negative_min_initial_deposit_fails() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+
+    env.register(
+        StrategyVaultContract,
+        (
+            admin.clone(),
+            String::from_str(&env, "Vault Shares"),
+            String::from_str(&env, "vTKN"),
+            token.address(),
+            0u32,
+            Vec::<Address>::new(&env),
+            LOCK_TIME,
+            -1_i128,
+            STRATEGY_DELAY,
+            LaunchConfig {
+                seed: LaunchSeed::None,
+                deposit_cap: LaunchCap::None,
+            },
+        ),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #429)")] // StrategyAlreadyAdded
+fn test_duplicate_constructor_strategies_fail() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    let strategy = Address::generate(&env);
+
+    register_vault(
+        &env,
+        &admin,
+        &token.address(),
+        Vec::from_array(&env, [strategy.clone(), strategy]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #446)")] // InvalidStrategy
+fn test_asset_as_constructor_strategy_fails() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+
+    register_vault(
+        &env,
+        &admin,
+        &token.address(),
+        Vec::from_array(&env, [token.address()]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #446)")] // InvalidStrategy
+fn test_vault_cannot_add_itself_as_strategy() {
+    let (_env, vault, _, _, _) = setup_test();
+
+    vault.add_strategy(&vault.address);
+}
+
+#[test]
+fn test_constructor_seed_deposit_mints_first_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let seeder = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &token.address()).mint(&seeder, &(1000 * SCALAR_7));
+
+    let vault = StrategyVaultContractClient::new(
+        &env,
+        &env.register(
+            StrategyVaultContract,
+            (
+                admin,
+                String::from_str(&env, "Vault Shares"),
+                String::from_str(&env, "vTKN"),
+                token.address(),
+                0u32,
+                Vec::<Address>::new(&env),
+                LOCK_TIME,
+                MIN_INITIAL_DEPOSIT,
+                STRATEGY_DELAY,
+                LaunchConfig {
+                    seed: LaunchSeed::Deposit(SeedDeposit {
+                        depositor: seeder.clone(),
+                        amount: 1000 * SCALAR_7,
+                    }),
+                    deposit_cap: LaunchCap::None,
+                },
+            ),
+        ),
+    );
+
+    assert_eq!(vault.total_assets(), 1000 * SCALAR_7);
+    assert_eq!(vault.total_supply(), 1000 * SCALAR_7);
+    assert_eq!(vault.balance(&seeder), 1000 * SCALAR_7 - BOOTSTRAP_SHARES);
+    assert_eq!(vault.balance(&vault.address), BOOTSTRAP_SHARES);
+}
+
+#[test]
+fn test_deposit_cap_grows_on_launch_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &token.address()).mint(&user, &(100_000 * SCALAR_7));
+    let week = 7 * 86_400;
+
+    let vault = StrategyVaultContractClient::new(
+        &env,
+        &env.register(
+            StrategyVaultContract,
+            (
+                admin,
+                String::from_str(&env, "Vault Shares"),
+                String::from_str(&env, "vTKN"),
+                token.address(),
+                0u32,
+                Vec::<Address>::new(&env),
+                LOCK_TIME,
+                MIN_INITIAL_DEPOSIT,
+                STRATEGY_DELAY,
+                LaunchConfig {
+                    seed: LaunchSeed::None,
+                    deposit_cap: LaunchCap::Schedule(DepositCap {
+                        initial: 10_000 * SCALAR_7,
+                        step: 5000 * SCALAR_7,
+                        period: week,
+                        start: env.ledger().timestamp(),
+                    }),
+                },
+            ),
+        ),
+    );
+
+    assert_eq!(vault.deposit_cap(), Some(10_000 * SCALAR_7));
+    vault.deposit(&(8000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.max_deposit(&user), 2000 * SCALAR_7);
+    assert_eq!(vault.max_mint(&user), 2000 * SCALAR_7);
+    assert_eq!(
+        vault
+            .try_deposit(&(2000 * SCALAR_7 + 1), &user, &user, &user)
+            .err(),
+        Some(Ok(StrategyVaultError::DepositCapExceeded.into()))
+    );
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + week);
+    assert_eq!(vault.deposit_cap(), Some(15_000 * SCALAR_7));
+    vault.deposit(&(7000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.max_deposit(&user), 0);
+}
+
+#[test]
+fn test_deposit_with_id_rejects_repeated_operation() {
+    let (env, vault, _, user, _) = setup_test();
+    let op_id = BytesN::from_array(&env, &[7; 32]);
+
+    let shares = vault.deposit_with_id(&(1000 * SCALAR_7), &user, &user, &user, &op_id);
+    assert!(vault.operation_used(&user, &op_id));
+    assert_eq!(
+        vault
+            .try_deposit_with_id(&(1000 * SCALAR_7), &user, &user, &user, &op_id)
+            .err(),
+        Some(Ok(StrategyVaultError::DuplicateOperation.into()))
+    );
+    assert_eq!(vault.balance(&user), shares);
+
+    vault.deposit_with_id(
+        &(1000 * SCALAR_7),
+        &user,
+        &user,
+        &user,
+        &BytesN::from_array(&env, &[8; 32]),
+    );
+    assert!(vault.balance(&user) > shares);
+}
+
+#[test]
+fn test_operations_with_deadline_fail_once_expired() {
+    let (env, vault, _, user, _) = setup_test();
+    let deadline = env.ledger().timestamp() + 60;
+
+    let shares = vault.deposit_with_deadline(&(1000 * SCALAR_7), &user, &user, &user, &deadline);
+    vault.mint_with_deadline(&shares, &user, &user, &user, &deadline);
+    vault.redeem_with_deadline(&shares, &user, &user, &user, &deadline);
+
+    env.ledger().set_timestamp(deadline + 1);
+    let expired = Some(Ok(StrategyVaultError::Expired.into()));
+    assert_eq!(
+        vault
+            .try_deposit_with_deadline(&SCALAR_7, &user, &user, &user, &deadline)
+            .err(),
+        expired
+    );
+    assert_eq!(
+        vault
+            .try_mint_with_deadline(&SCALAR_7, &user, &user, &user, &deadline)
+            .err(),
+        expired
+    );
+    assert_eq!(
+        vault
+            .try_redeem_with_deadline(&SCALAR_7, &user, &user, &user, &deadline)
+            .err(),
+        expired
+    );
+    assert_eq!(vault.balance(&user), shares);
+}
+
+#[test]
+fn test_first_deposit_locks_bootstrap_shares() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.balance(&vault.address), BOOTSTRAP_SHARES);
+    assert_eq!(vault.balance(&user), shares);
+    assert_eq!(vault.total_supply(), shares + BOOTSTRAP_SHARES);
+}
+
+#[test]
+fn test_later_deposits_do_not_lock_shares() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.balance(&vault.address), BOOTSTRAP_SHARES);
+    assert_eq!(
+        vault.total_supply(),
+        vault.balance(&user) + BOOTSTRAP_SHARES
+    );
+    assert!(shares > 0);
+}
+
+#[test]
+fn test_first_mint_locks_bootstrap_shares() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.mint(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.balance(&vault.address), BOOTSTRAP_SHARES);
+    assert_eq!(vault.balance(&user), 1000 * SCALAR_7 - BOOTSTRAP_SHARES);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #423)")] // InitialDepositTooSmall
+fn test_first_deposit_below_minimum_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(MIN_INITIAL_DEPOSIT - 1), &user, &user, &user);
+}
+
+#[test]
+fn test_full_exit_keeps_bootstrap_supply() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.redeem(&shares, &user, &user, &user);
+
+    assert_eq!(vault.total_supply(), BOOTSTRAP_SHARES);
+}
+
+#[test]
+fn test_min_supply_floor_limits_redemptions_until_wind_down() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.set_min_supply(&(1000 * SCALAR_7));
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::MinSupplySet(client::MinSupplySetEvent {
+            min_supply: 1000 * SCALAR_7,
+        })]
+    );
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let redeemable = vault.total_supply() - 1000 * SCALAR_7;
+    assert_eq!(vault.max_redeem(&user), redeemable);
+    assert_eq!(
+        vault.try_redeem(&shares, &user, &user, &user).err(),
+        Some(Ok(StrategyVaultError::SupplyFloorBreached.into()))
+    );
+
+    vault.redeem(&redeemable, &user, &user, &user);
+    assert_eq!(vault.total_supply(), 1000 * SCALAR_7);
+    assert_eq!(vault.max_withdraw(&user), 0);
+
+    vault.wind_down();
+    assert_eq!(vault.max_redeem(&user), vault.balance(&user));
+    vault.redeem(&vault.balance(&user), &user, &user, &user);
+    assert_eq!(vault.total_supply(), BOOTSTRAP_SHARES);
+}
+
+#[test]
+fn test_deposit_minting_zero_shares_fails() {
+    let (env, vault, token, user, _) = setup_test();
+
+    vault.deposit(&SCALAR_7, &user, &user, &user);
+    StellarAssetClient::new(&env, &token).mint(&user, &(77_777 * SCALAR_7));
+    vault.donate(&user, &(77_777 * SCALAR_7));
+    assert_eq!(vault.preview_deposit(&999), 0);
+
+    assert_eq!(
+        vault.try_deposit(&999, &user, &user, &user).err(),
+        Some(Ok(StrategyVaultError::ZeroSharesMinted.into()))
+    );
+    assert_eq!(vault.deposit(&SCALAR_7, &user, &user, &user), 128);
+}
+
+#[test]
+fn test_redeem_returning_zero_tokens_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(9999 * SCALAR_7));
+    assert_eq!(vault.preview_redeem(&1), 0);
+
+    assert_eq!(
+        vault.try_redeem(&1, &user, &user, &user).err(),
+        Some(Ok(StrategyVaultError::ZeroTokensReturned.into()))
+    );
+    assert_eq!(vault.redeem(&10_000, &user, &user, &user), 1);
+}
+
+// ==================== Convenience Entrypoint Tests ====================
+
+#[test]
+fn test_deposit_all_and_redeem_max() {
+    let (env, vault, token, user, _) = setup_test();
+    let token_client = TokenClient::new(&env, &token);
+
+    let shares = vault.deposit_all(&user, &user);
+    assert_eq!(token_client.balance(&user), 0);
+    assert_eq!(vault.balance(&user), shares);
+
+    let assets = vault.redeem_max(&user, &user);
+    assert_eq!(vault.balance(&user), 0);
+    assert_eq!(token_client.balance(&user), assets);
+}
+
+#[test]
+fn test_redeem_max_respects_withdraw_capacity() {
+    let (env, vault, token, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_withdraw_limit(&WithdrawLimit {
+        limit: 1000 * SCALAR_7,
+        window: 3600,
+    });
+    let assets = vault.redeem_max(&user, &user);
+
+    assert!(assets <= 1000 * SCALAR_7 && assets > 999 * SCALAR_7);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&user),
+        90_000 * SCALAR_7 + assets
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #420)")] // InvalidAmount
+fn test_deposit_all_with_empty_balance_fails() {
+    let (env, vault, _, _, _) = setup_test();
+
+    vault.deposit_all(&Address::generate(&env), &Address::generate(&env));
+}
+
+// ==================== Migration Tests ====================
+
+#[test]
+fn test_migrate_to_moves_position_to_new_vault() {
+    let (env, vault, token, user, _) = setup_test();
+    let new_vault = StrategyVaultContractClient::new(
+        &env,
+        &register_vault(&env, &vault.admin(), &token, Vec::new(&env)),
+    );
+    let seeder = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&seeder, &(1000 * SCALAR_7));
+    new_vault.deposit(&(1000 * SCALAR_7), &seeder, &seeder, &seeder);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let new_shares = vault.migrate_to(&new_vault.address, &user, &shares);
+
+    assert_eq!(vault.balance(&user), 0);
+    assert_eq!(new_vault.balance(&user), new_shares);
+    assert!(new_vault.convert_to_assets(&new_shares) > 9999 * SCALAR_7);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&user),
+        90_000 * SCALAR_7
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #436)")] // AssetMismatch
+fn test_migrate_to_vault_of_other_asset_fails() {
+    let (env, vault, _, user, _) = setup_test();
+    let other = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let new_vault = register_vault(&env, &vault.admin(), &other.address(), Vec::new(&env));
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.migrate_to(&new_vault, &user, &shares);
+}
+
+// ==================== Operator Tests ====================
+
+#[test]
+fn test_approved_operator_redeems_to_owner() {
+    let (env, vault, token, user, _) = setup_test();
+    let operator = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_operator(&user, &operator, &true);
+    assert!(vault.is_operator(&user, &operator));
+
+    let assets = vault.redeem(&(shares / 2), &user, &user, &operator);
+    assert_eq!(
+        env.auths()[0].0,
+        operator,
+        "the operator authorizes instead of the owner"
+    );
+    assert_eq!(vault.balance(&user), shares - shares / 2);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&user),
+        90_000 * SCALAR_7 + assets
+    );
+
+    let shares_burned = vault.withdraw(&(1000 * SCALAR_7), &user, &user, &operator);
+    assert_eq!(vault.balance(&user), shares - shares / 2 - shares_burned);
+}
+
+#[test]
+fn test_operator_cannot_redeem_to_itself_without_allowance() {
+    let (env, vault, _, user, _) = setup_test();
+    let operator = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_operator(&user, &operator, &true);
+
+    assert!(vault
+        .try_redeem(&shares, &operator, &user, &operator)
+        .is_err());
+}
+
+#[test]
+fn test_revoked_operator_cannot_redeem() {
+    let (env, vault, _, user, _) = setup_test();
+    let operator = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_operator(&user, &operator, &true);
+    vault.set_operator(&user, &operator, &false);
+
+    assert!(!vault.is_operator(&user, &operator));
+    assert!(vault.try_redeem(&shares, &user, &user, &operator).is_err());
+}
+
+// ==================== Share Lock Tests ====================
+
+#[test]
+fn test_owner_cannot_move_escrowed_shares() {
+    let (env, vault, _, user, _) = setup_test();
+    let lender = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME + 1);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    vault.approve_locked(&user, &lender, &(shares / 2), &expiration_ledger);
+
+    assert_eq!(vault.locked_shares(&user), shares / 2);
+    assert_eq!(vault.allowance(&user, &lender), shares / 2);
+    assert_eq!(vault.max_redeem(&user), shares - shares / 2);
+    let escrowed = Some(Ok(StrategyVaultError::SharesEscrowed.into()));
+    assert_eq!(
+        vault.try_redeem(&shares, &user, &user, &user).err(),
+        escrowed
+    );
+    assert_eq!(
+        vault
+            .try_transfer(&user, Address::generate(&env), &shares)
+            .err(),
+        escrowed
+    );
+    assert_eq!(
+        vault
+            .try_approve(&user, &lender, &0, &expiration_ledger)
+            .err(),
+        escrowed
+    );
+    assert_eq!(
+        vault
+            .try_approve_locked(&user, &Address::generate(&env), &1, &expiration_ledger)
+            .err(),
+        escrowed
+    );
+
+    vault.redeem(&(shares - shares / 2), &user, &user, &user);
+    assert_eq!(vault.balance(&user), shares / 2);
+}
+
+#[test]
+fn test_owner_cannot_escrow_shares_for_themselves() {
+    let (env, vault, _, user, _) = setup_test();
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    assert_eq!(
+        vault
+            .try_approve_locked(&user, &user, &shares, &expiration_ledger)
+            .err(),
+        Some(Ok(StrategyVaultError::SelfApproval.into()))
+    );
+    assert_eq!(vault.share_lock(&user), None);
+}
+
+#[test]
+fn test_lock_spender_takes_escrowed_shares() {
+    let (env, vault, _, user, _) = setup_test();
+    let lender = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME + 1);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    vault.approve_locked(&user, &lender, &(shares / 2), &expiration_ledger);
+    assert_eq!(
+        decoded_events(&env, &vault.address).last().cloned(),
+        Some(VaultEvent::ShareLockSet(client::ShareLockSetEvent {
+            owner: ScAddress::from(&user),
+            spender: ScAddress::from(&lender),
+            shares: shares / 2,
+            expiration_ledger,
+        }))
+    );
+
+    vault.transfer_from(&lender, &user, &lender, &(shares / 4));
+    assert_eq!(vault.locked_shares(&user), shares / 2 - shares / 4);
+
+    vault.redeem(&(shares / 2 - shares / 4), &lender, &user, &lender);
+    assert_eq!(vault.share_lock(&user), None);
+    vault.redeem(&vault.balance(&user), &user, &user, &user);
+}
+
+#[test]
+fn test_released_or_expired_lock_frees_shares() {
+    let (env, vault, _, user, _) = setup_test();
+    let lender = Address::generate(&env);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME + 1);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    vault.approve_locked(&user, &lender, &shares, &expiration_ledger);
+
+    vault.release_lock(&user);
+    assert_eq!(
+        env.auths()[0].0,
+        lender,
+        "the spender releases the escrowed shares"
+    );
+    assert_eq!(vault.locked_shares(&user), 0);
+    assert_eq!(vault.allowance(&user, &lender), 0);
+    assert_eq!(
+        vault.try_release_lock(&user).err(),
+        Some(Ok(StrategyVaultError::NoActiveLock.into()))
+    );
+
+    vault.approve_locked(&user, &lender, &shares, &expiration_ledger);
+    env.ledger().set_sequence_number(expiration_ledger + 1);
+    assert_eq!(vault.share_lock(&user), None);
+    assert_eq!(vault.max_redeem(&user), shares);
+    assert_eq!(
+        vault.try_release_lock(&user).err(),
+        Some(Ok(StrategyVaultError::NoActiveLock.into()))
+    );
+    vault.transfer(&user, &lender, &shares);
+}
+
+// ==================== Allowlist Tests ====================
+
+/// Builds a three-leaf allowlist and returns its root with a proof for each member
+fn allowlist(env: &Env, members: &[Address; 3]) -> (BytesN<32>, [Vec<BytesN<32>>; 3]) {
+    let [a, b, c] = members.each_ref().map(|member| merkle::leaf(env, member));
+    let ab = merkle::hash_pair(env, &a, &b);
+    let root = merkle::hash_pair(env, &ab, &c);
+    let proofs = [
+        Vec::from_array(env, [b, c.clone()]),
+        Vec::from_array(env, [a, c]),
+        Vec::from_array(env, [ab]),
+    ];
+    (root, proofs)
+}
+
+#[test]
+fn test_deposit_with_proof_admits_allowlisted_receivers() {
+    let (env, vault, _, user, _) = setup_test();
+    let members = [
+        user.clone(),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let (root, proofs) = allowlist(&env, &members);
+
+    vault.set_deposit_root(&Some(root.clone()));
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::DepositRootSet(client::DepositRootSetEvent {
+            root: Some(root.to_array()),
+        })]
+    );
+    assert_eq!(vault.deposit_root(), Some(root));
+
+    vault.deposit_with_proof(&(10_000 * SCALAR_7), &user, &user, &user, &proofs[0]);
+    let shares =
+        vault.deposit_with_proof(&(1000 * SCALAR_7), &members[2], &user, &user, &proofs[2]);
+    assert_eq!(vault.balance(&members[2]), shares);
+
+    // A valid proof for someone else does not admit a different receiver
+    let result =
+        vault.try_deposit_with_proof(&(1000 * SCALAR_7), &members[2], &user, &user, &proofs[1]);
+    assert!(result.is_err());
+
+    vault.set_deposit_root(&None);
+    vault.deposit(&(1000 * SCALAR_7), &Address::generate(&env), &user, &user);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #437)")] // NotAllowlisted
+fn test_gated_deposit_without_proof_fails() {
+    let (env, vault, _, user, _) = setup_test();
+    let members = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let (root, _) = allowlist(&env, &members);
+
+    vault.set_deposit_root(&Some(root));
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+}
+
+/// Enables coupons on `vault` and returns a function signing coupons for it
+fn coupon_signer(
+    env: &Env,
+    vault: &StrategyVaultContractClient,
+) -> impl Fn(&DepositCoupon) -> BytesN<64> {
+    let key = SigningKey::from_bytes(&[7; 32]);
+    vault.set_coupon_signer(&Some(BytesN::from_array(
+        env,
+        &key.verifying_key().to_bytes(),
+    )));
+    let (env, vault) = (env.clone(), vault.address.clone());
+    move |coupon| {
+        let message = (vault.clone(), coupon.clone()).to_xdr(&env);
+        let signature = key.sign(&message.iter().collect::<std::vec::Vec<u8>>());
+        BytesN::from_array(&env, &signature.to_bytes())
+    }
+}
+
+#[test]
+fn test_deposit_with_coupon_up_to_max_amount() {
+    let (env, vault, _, user, _) = setup_test();
+    let sign = coupon_signer(&env, &vault);
+    let coupon = DepositCoupon {
+        receiver: user.clone(),
+        max_amount: 15_000 * SCALAR_7,
+        expiry: env.ledger().timestamp() + 3600,
+    };
+    let signature = sign(&coupon);
+
+    let shares = vault.deposit_with_coupon(&(10_000 * SCALAR_7), &user, &user, &coupon, &signature);
+    assert_eq!(vault.balance(&user), shares);
+    vault.deposit_with_coupon(&(5000 * SCALAR_7), &user, &user, &coupon, &signature);
+    assert_eq!(vault.coupon_used(&user), 15_000 * SCALAR_7);
+
+    let result = vault.try_deposit_with_coupon(&1, &user, &user, &coupon, &signature);
+    assert_eq!(
+        result.err(),
+        Some(Ok(StrategyVaultError::CouponAmountExceeded.into()))
+    );
+}
+
+#[test]
+fn test_coupon_signer_makes_plain_deposits_invite_only() {
+    let (env, vault, _, user, _) = setup_test();
+    let sign = coupon_signer(&env, &vault);
+    let not_allowlisted = Some(Ok(StrategyVaultError::NotAllowlisted.into()));
+
+    // Without a root, only coupon holders can deposit, so `max_amount` cannot be skipped
+    assert_eq!(
+        vault.try_deposit(&SCALAR_7, &user, &user, &user).err(),
+        not_allowlisted
+    );
+    assert_eq!(
+        vault.try_mint(&SCALAR_7, &user, &user, &user).err(),
+        not_allowlisted
+    );
+    let coupon = DepositCoupon {
+        receiver: user.clone(),
+        max_amount: 1000 * SCALAR_7,
+        expiry: env.ledger().timestamp() + 3600,
+    };
+    vault.deposit_with_coupon(&(1000 * SCALAR_7), &user, &user, &coupon, &sign(&coupon));
+
+    // With a root as well, allowlisted receivers still deposit with a proof
+    let members = [
+        user.clone(),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let (root, proofs) = allowlist(&env, &members);
+    vault.set_deposit_root(&Some(root));
+    vault.deposit_with_proof(&SCALAR_7, &user, &user, &user, &proofs[0]);
+    assert_eq!(
+        vault
+            .try_deposit(&SCALAR_7, &members[1], &user, &user)
+            .err(),
+        not_allowlisted
+    );
+}
+
+#[test]
+fn test_deposit_with_coupon_rejects_expired_and_forged_coupons() {
+    let (env, vault, _, user, _) = setup_test();
+    let sign = coupon_signer(&env, &vault);
+    let coupon = DepositCoupon {
+        receiver: user.clone(),
+        max_amount: 15_000 * SCALAR_7,
+        expiry: env.ledger().timestamp() + 3600,
+    };
+    let signature = sign(&coupon);
+
+    // Raising the amount invalidates the signature
+    let forged = DepositCoupon {
+        max_amount: 100_000 * SCALAR_7,
+        ..coupon.clone()
+    };
+    let result =
+        vault.try_deposit_with_coupon(&(10_000 * SCALAR_7), &user, &user, &forged, &signature);
+    assert!(result.is_err());
+
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result =
+        vault.try_deposit_with_coupon(&(10_000 * SCALAR_7), &user, &user, &coupon, &signature);
+    assert_eq!(
+        result.err(),
+        Some(Ok(StrategyVaultError::CouponExpired.into()))
+    );
+}
+
+// ==================== Donation Tests ====================
+
+#[test]
+fn test_donate_raises_share_price_without_minting() {
+    let (env, vault, _, user, _) = setup_test();
+    let sponsor = Address::generate(&env);
+    StellarAssetClient::new(&env, &vault.query_asset()).mint(&sponsor, &(1000 * SCALAR_7));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let supply = vault.total_supply();
+    let user_assets = vault.convert_to_assets(&vault.balance(&user));
+
+    vault.donate(&sponsor, &(1000 * SCALAR_7));
+
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::Donation(client::DonationEvent {
+            from: ScAddress::from(&sponsor),
+            amount: 1000 * SCALAR_7,
+        })]
+    );
+    assert_eq!(vault.total_supply(), supply);
+    assert_eq!(vault.total_assets(), 11_000 * SCALAR_7);
+    assert!(vault.convert_to_assets(&vault.balance(&user)) > user_assets);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #420)")] // InvalidAmount
+fn test_donate_zero_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.donate(&user, &0);
+}
+
+#[test]
+fn test_donate_before_first_deposit_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    assert_eq!(
+        vault.try_donate(&user, &(1000 * SCALAR_7)).err(),
+        Some(Ok(StrategyVaultError::VaultEmpty.into()))
+    );
+    assert_eq!(vault.total_assets(), 0);
+
+    // The first depositor is priced as if nobody had tried to donate
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.total_supply(), 1000 * SCALAR_7);
+}
+
+#[test]
+fn test_donate_after_wind_down_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.wind_down();
+
+    assert_eq!(
+        vault.try_donate(&user, &SCALAR_7).err(),
+        Some(Ok(StrategyVaultError::VaultWoundDown.into()))
+    );
+}
+
+// ==================== Metadata Tests ====================
+
+#[test]
+fn test_metadata_defaults_to_share_name() {
+    let (env, vault, _, _, _) = setup_test();
+
+    let metadata = vault.get_metadata();
+    assert_eq!(metadata.name, String::from_str(&env, "Vault Shares"));
+    assert_eq!(metadata.description, String::from_str(&env, ""));
+    assert_eq!(metadata.uri, String::from_str(&env, ""));
+}
+
+#[test]
+fn test_admin_sets_metadata() {
+    let (env, vault, _, _, _) = setup_test();
+    let metadata = VaultMetadata {
+        name: String::from_str(&env, "USDC Yield"),
+        description: String::from_str(&env, "Lends USDC to vetted strategies"),
+        uri: String::from_str(&env, "https://example.com/vault.json"),
+    };
+
+    let admin = vault.admin();
+    vault.set_metadata(&metadata);
+
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::MetadataUpdated(client::MetadataUpdatedEvent {
+            name: "USDC Yield".into(),
+            description: "Lends USDC to vetted strategies".into(),
+            uri: "https://example.com/vault.json".into(),
+        })]
+    );
+    assert_eq!(vault.get_metadata(), metadata);
+}
+
+// ==================== TTL Tests ====================
+
+#[test]
+fn test_extend_user_ttl_bumps_user_entries() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.set_receiver_hook(&user, &true);
+    let ttl = |key: StrategyStorageKey| {
+        env.as_contract(&vault.address, || env.storage().persistent().get_ttl(&key))
+    };
+    let initial = ttl(StrategyStorageKey::LastDepositTime(user.clone()));
+
+    env.ledger()
+        .set_sequence_number(env.ledger().sequence() + initial - 10);
+    assert_eq!(ttl(StrategyStorageKey::LastDepositTime(user.clone())), 10);
+
+    vault.extend_user_ttl(&user);
+
+    assert_eq!(
+        ttl(StrategyStorageKey::LastDepositTime(user.clone())),
+        initial
+    );
+    assert_eq!(ttl(StrategyStorageKey::ReceiverHook(user.clone())), initial);
+}
+
+#[test]
+fn test_extend_user_ttl_without_entries_is_noop() {
+    let (env, vault, _, _, _) = setup_test();
+
+    vault.extend_user_ttl(&Address::generate(&env));
+}
+
+// ==================== Withdraw Limit Tests ====================
+
+#[test]
+fn test_withdraw_limit_disabled_by_default() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.withdraw_capacity(), i128::MAX);
+    assert_eq!(vault.max_redeem(&user), shares);
+}
+
+#[test]
+fn test_withdraw_limit_caps_window_and_resets() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_withdraw_limit(&WithdrawLimit {
+        limit: 1000 * SCALAR_7,
+        window: 3600,
+    });
+
+    vault.withdraw(&(600 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.withdraw_capacity(), 400 * SCALAR_7);
+    assert_eq!(vault.max_withdraw(&user), 400 * SCALAR_7);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3600);
+    assert_eq!(vault.withdraw_capacity(), 1000 * SCALAR_7);
+    vault.withdraw(&(1000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.withdraw_capacity(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #427)")] // TryLater
+fn test_redeem_above_withdraw_limit_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.set_withdraw_limit(&WithdrawLimit {
+        limit: 1000 * SCALAR_7,
+        window: 3600,
+    });
+
+    vault.redeem(&(2000 * SCALAR_7), &user, &user, &user);
+}
+
+// ==================== Native XLM Tests ====================
+
+/// Deploys the native XLM Stellar Asset Contract
+fn register_native_asset(env: &Env) -> Address {
+    let create = HostFunction::CreateContract(CreateContractArgs {
+        contract_id_preimage: ContractIdPreimage::Asset(Asset::Native),
+        executable: ContractExecutable::StellarAsset,
+    });
+    let ScVal::Address(address) = env.host().invoke_function(create).unwrap() else {
+        panic!("asset contract id is not an address");
+    };
+    Address::try_from_val(env, &address).unwrap()
+}
+
+/// Creates a classic account, identified by `seed`, holding `balance` stroops of XLM
+fn create_account(env: &Env, seed: u8, balance: i128) -> Address {
+    let id = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([seed; 32])));
+    let key = LedgerKey::Account(LedgerKeyAccount {
+        account_id: id.clone(),
+    });
+    let entry = LedgerEntry {
+        data: LedgerEntryData::Account(AccountEntry {
+            account_id: id.clone(),
+            balance: balance as i64,
+            flags: 0,
+            home_domain: Default::default(),
+            inflation_dest: None,
+            num_sub_entries: 0,
+            seq_num: SequenceNumber(0),
+            thresholds: Thresholds([1; 4]),
+            signers: Default::default(),
+            ext: AccountEntryExt::V0,
+        }),
+        last_modified_ledger_seq: 0,
+        ext: LedgerEntryExt::V0,
+    };
+    env.host()
+        .add_ledger_entry(&Rc::new(key), &Rc::new(entry), None)
+        .unwrap();
+    Address::try_from_val(env, &ScAddress::Account(id)).unwrap()
+}
+
+#[test]
+fn test_xlm_vault_deposit_and_redeem() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let xlm = register_native_asset(&env);
+    let user = create_account(&env, 1, 1000 * SCALAR_7);
+    let vault_address = register_vault(&env, &Address::generate(&env), &xlm, Vec::new(&env));
+    let vault = StrategyVaultContractClient::new(&env, &vault_address);
+    let xlm_client = TokenClient::new(&env, &xlm);
+
+    assert_eq!(vault.decimals(), 7);
+    let shares = vault.deposit(&(500 * SCALAR_7), &user, &user, &user);
+    assert_eq!(xlm_client.balance(&user), 500 * SCALAR_7);
+    assert_eq!(vault.total_assets(), 500 * SCALAR_7);
+
+    let assets = vault.redeem(&shares, &user, &user, &user);
+    assert_eq!(xlm_client.balance(&user), 500 * SCALAR_7 + assets);
+    // Only the bootstrap shares' assets stay in the vault
+    assert_eq!(vault.total_assets(), 500 * SCALAR_7 - assets);
+    assert_eq!(vault.total_supply(), BOOTSTRAP_SHARES);
+}
+
+#[test]
+fn test_xlm_account_reserve_cannot_be_deposited() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_base_reserve(SCALAR_7 as u32 / 2);
+    let xlm = register_native_asset(&env);
+    let user = create_account(&env, 1, 1000 * SCALAR_7);
+    let vault_address = register_vault(&env, &Address::generate(&env), &xlm, Vec::new(&env));
+    let vault = StrategyVaultContractClient::new(&env, &vault_address);
+
+    // The account balance includes its 1 XLM minimum reserve (2 base reserves)
+    assert!(vault.try_deposit_all(&user, &user).is_err());
+    assert!(vault
+        .try_deposit(&(999 * SCALAR_7 + 1), &user, &user, &user)
+        .is_err());
+    vault.deposit(&(999 * SCALAR_7), &user, &user, &user);
+    assert_eq!(TokenClient::new(&env, &xlm).balance(&user), SCALAR_7);
+}
+
+// ==================== Balance-Delta Tests ====================
+
+fn setup_fee_token_vault<'a>() -> (Env, StrategyVaultContractClient<'a>, FeeTokenClient<'a>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let token = FeeTokenClient::new(&env, &env.register(FeeToken, ()));
+    let strategy = Address::generate(&env);
+    let strategies = Vec::from_array(&env, [strategy.clone()]);
+    let vault_address = register_vault(&env, &Address::generate(&env), &token.address, strategies);
+    let vault = StrategyVaultContractClient::new(&env, &vault_address);
+    vault.set_exposure_cap(&strategy, &None);
+    vault.set_balance_delta(&true);
+    (env, vault, token)
+}
+
+#[test]
+fn test_balance_delta_credits_received_assets() {
+    let (env, vault, token) = setup_fee_token_vault();
+    let user = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    token.mint(&user, &(1000 * SCALAR_7));
+    token.mint(&user2, &(1000 * SCALAR_7));
+    assert!(vault.balance_delta());
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.total_assets(), 990 * SCALAR_7);
+
+    let expected = vault.preview_deposit(&(990 * SCALAR_7));
+    let shares = vault.deposit(&(1000 * SCALAR_7), &user2, &user2, &user2);
+    assert_eq!(shares, expected);
+    assert_eq!(vault.total_assets(), 1980 * SCALAR_7);
+    // The first depositor's position is not diluted by the second deposit's fee
+    assert_eq!(
+        vault.convert_to_assets(&vault.balance(&user)) + vault.convert_to_assets(&BOOTSTRAP_SHARES),
+        990 * SCALAR_7
+    );
+}
+
+#[test]
+fn test_balance_delta_credits_received_strategy_returns() {
+    let (env, vault, token) = setup_fee_token_vault();
+    let user = Address::generate(&env);
+    let strategy = vault.strategies().get(0).unwrap();
+    token.mint(&user, &(1000 * SCALAR_7));
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(500 * SCALAR_7));
+    assert_eq!(token.balance(&strategy), 495 * SCALAR_7);
+    vault.strategy_deposit(&strategy, &(495 * SCALAR_7));
+
+    let data = vault.get_strategy(&strategy);
+    assert_eq!(data.total_received, 49005 * SCALAR_7 / 100);
+    assert_eq!(vault.total_assets(), 490 * SCALAR_7 + data.total_received);
+}
+
+#[test]
+fn test_balance_delta_reports_received_strategy_gain() {
+    let (env, vault, token) = setup_fee_token_vault();
+    let user = Address::generate(&env);
+    let strategy = vault.strategies().get(0).unwrap();
+    token.mint(&user, &(1000 * SCALAR_7));
+    token.mint(&strategy, &(105 * SCALAR_7));
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(500 * SCALAR_7));
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 10));
+    // 100 profit: 10 is kept and 590 is sent, of which the vault receives 584.1
+    vault.strategy_deposit(&strategy, &(600 * SCALAR_7));
+
+    assert_eq!(
+        decoded_events(&env, &vault.address).last(),
+        Some(&VaultEvent::StrategyReport(client::StrategyReportEvent {
+            strategy: ScAddress::from(&strategy),
+            gain: 841 * SCALAR_7 / 10,
+            loss: 0,
+            fees: 10 * SCALAR_7,
+            debt: 0,
+        }))
+    );
+    assert_eq!(
+        vault.get_strategy(&strategy).net_impact,
+        841 * SCALAR_7 / 10
+    );
+}
+
+#[test]
+fn test_balance_delta_prices_like_standard_deposit() {
+    let (env, vault, token, user, _) = setup_test();
+    let user2 = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&user2, &(1000 * SCALAR_7));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.donate(&user, &1_234_567);
+    vault.set_balance_delta(&true);
+
+    // Without a transfer fee both accounting modes mint exactly the previewed shares
+    let assets = 777 * SCALAR_7 + 7;
+    let expected = vault.preview_deposit(&assets);
+    assert_eq!(vault.deposit(&assets, &user2, &user2, &user2), expected);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #435)")] // TransferShortfall
+fn test_balance_delta_mint_with_fee_fails() {
+    let (env, vault, token) = setup_fee_token_vault();
+    let user = Address::generate(&env);
+    token.mint(&user, &(1000 * SCALAR_7));
+
+    vault.mint(&(100 * SCALAR_7), &user, &user, &user);
+}
+
+// ==================== Wind-Down Tests ====================
+
+#[test]
+fn test_wind_down_recalls_and_pays_out_pro_rata() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let user2 = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&user2, &(10_000 * SCALAR_7));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.deposit(&(10_000 * SCALAR_7), &user2, &user2, &user2);
+    vault.strategy_withdraw(&strategy, &(5000 * SCALAR_7));
+    vault.set_withdraw_limit(&WithdrawLimit {
+        limit: 1000 * SCALAR_7,
+        window: 3600,
+    });
+
+    vault.wind_down();
+    assert!(vault.is_wound_down());
+    assert_eq!(vault.max_deposit(&user), 0);
+    assert_eq!(vault.max_mint(&user), 0);
+
+    vault.strategy_deposit(&strategy, &(5000 * SCALAR_7));
+    let token_client = TokenClient::new(&env, &token);
+    let before = token_client.balance(&user2);
+    let assets = vault.claim_wind_down(&user2);
+
+    assert_eq!(vault.balance(&user2), 0);
+    assert_eq!(token_client.balance(&user2) - before, assets);
+    assert!(assets > 9999 * SCALAR_7 && assets <= 10_000 * SCALAR_7);
+}
+
+#[test]
+fn test_claim_wind_down_leaves_escrowed_shares_to_spender() {
+    let (env, vault, token, user, _) = setup_test();
+    let lender = Address::generate(&env);
+    let token_client = TokenClient::new(&env, &token);
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    vault.approve_locked(&user, &lender, &(shares / 4), &expiration_ledger);
+    vault.wind_down();
+
+    let before = token_client.balance(&user);
+    let assets = vault.claim_wind_down(&user);
+    assert_eq!(token_client.balance(&user) - before, assets);
+    assert_eq!(vault.balance(&user), shares / 4);
+    assert_eq!(vault.locked_shares(&user), shares / 4);
+
+    // The lender takes the collateral, which releases the lock
+    let seized = vault.redeem(&(shares / 4), &lender, &user, &lender);
+    assert_eq!(token_client.balance(&lender), seized);
+    assert_eq!(vault.balance(&user), 0);
+    assert_eq!(vault.share_lock(&user), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #433)")] // VaultWoundDown
+fn test_deposit_after_wind_down_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.wind_down();
+    vault.deposit(&SCALAR_7, &user, &user, &user);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #433)")] // VaultWoundDown
+fn test_strategy_withdraw_after_wind_down_fails() {
+    let (_env, vault, _, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.wind_down();
+    vault.strategy_withdraw(&strategy, &SCALAR_7);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #434)")] // VaultNotWoundDown
+fn test_claim_wind_down_before_wind_down_fails() {
+    let (_env, vault, _, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.claim_wind_down(&user);
+}
+
+// ==================== Exit Fee Tests ====================
+
+#[test]
+fn test_exit_fee_decays_with_holding_time() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 50), // 2%
+        duration: 10 * 86_400,
+    });
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 50);
+
+    env.ledger().with_mut(|li| li.timestamp += 5 * 86_400);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 100);
+
+    env.ledger().with_mut(|li| li.timestamp += 5 * 86_400);
+    assert_eq!(vault.exit_fee_rate(&user), 0);
+}
+
+#[test]
+fn test_deposit_for_someone_else_keeps_their_running_fee_and_lock() {
+    let (env, vault, token, user, _) = setup_test();
+    let attacker = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&attacker, &(100_000 * SCALAR_7));
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 50), // 2%
+        duration: 10 * 86_400,
+    });
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+
+    // A deposit for the victim while their lock runs leaves the lock as it was
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    vault.deposit(&1, &user, &attacker, &attacker);
+    assert_eq!(vault.lock_duration(&user), LOCK_TIME - 100);
+
+    // Dust cannot push the decayed fee back up, even once it starts a new lock
+    env.ledger().with_mut(|li| li.timestamp += 5 * 86_400 - 100);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 100);
+    vault.deposit(&1, &user, &attacker, &attacker);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 100);
+    assert_eq!(vault.lock_duration(&user), LOCK_TIME);
+
+    // A large deposit ages the fee by its share of the position
+    vault.deposit(&(10_000 * SCALAR_7), &user, &attacker, &attacker);
+    let rate = vault.exit_fee_rate(&user);
+    assert!(rate > SCALAR_7 / 100 && rate < SCALAR_7 / 50);
+
+    // A deposit paid by the holder's own operator restarts both clocks
+    env.ledger().with_mut(|li| li.timestamp += LOCK_TIME);
+    vault.set_operator(&user, &attacker, &true);
+    vault.deposit(&1, &user, &attacker, &attacker);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 50);
+    assert_eq!(vault.lock_duration(&user), LOCK_TIME);
+}
+
+#[test]
+fn test_deposit_for_fresh_receiver_starts_lock() {
+    let (env, vault, _, user, _) = setup_test();
+    let receiver = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 50), // 2%
+        duration: 10 * 86_400,
+    });
+    vault.set_same_ledger_guard(&true);
+    let shares = vault.deposit(&(1000 * SCALAR_7), &receiver, &user, &user);
+
+    // Funded shares cannot leave in the same ledger, and stay locked against transfers
+    assert!(vault.is_locked(&receiver));
+    assert_eq!(vault.exit_fee_rate(&receiver), SCALAR_7 / 50);
+    assert_eq!(
+        vault
+            .try_redeem(&shares, &receiver, &receiver, &receiver)
+            .err(),
+        Some(Ok(StrategyVaultError::SameLedgerExit.into()))
+    );
+    env.ledger().with_mut(|li| li.timestamp += 5);
+    assert_eq!(
+        vault.try_transfer(&receiver, &recipient, &1).err(),
+        Some(Ok(StrategyVaultError::SharesLocked.into()))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += LOCK_TIME);
+    assert!(!vault.is_locked(&receiver));
+    vault.transfer(&receiver, &recipient, &1);
+}
+
+#[test]
+fn test_exit_fee_stays_with_remaining_holders() {
+    let (env, vault, token, user, _) = setup_test();
+    let user2 = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&user2, &(10_000 * SCALAR_7));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user2, &user2, &user2);
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let gross = vault.preview_redeem(&shares);
+
+    let assets = vault.redeem(&shares, &user, &user, &user);
+    assert_eq!(assets, gross - gross / 100);
+    assert_eq!(
+        decoded_events(&env, &vault.address).last(),
+        Some(&VaultEvent::ExitFeeCharged(client::ExitFeeChargedEvent {
+            owner: ScAddress::from(&user),
+            fee: gross / 100,
+        }))
+    );
+    assert_eq!(vault.total_assets(), 20_000 * SCALAR_7 - assets);
+}
+
+#[test]
+fn test_exit_fee_waived_after_wind_down() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(vault.exit_fee_rate(&user), SCALAR_7 / 100);
+
+    vault.wind_down();
+    assert_eq!(vault.exit_fee_rate(&user), 0);
+    let gross = vault.preview_redeem(&(shares / 2));
+    assert_eq!(vault.redeem(&(shares / 2), &user, &user, &user), gross);
+}
+
+#[test]
+fn test_withdraw_burns_extra_shares_for_exit_fee() {
+    let (env, vault, token, user, _) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let max = vault.max_withdraw(&user);
+    assert!(max < 9900 * SCALAR_7 && max > 9899 * SCALAR_7);
+
+    let burned = vault.withdraw(&(990 * SCALAR_7), &user, &user, &user);
+    assert!(burned >= vault.preview_withdraw(&(1000 * SCALAR_7)));
+    assert_eq!(vault.balance(&user), shares - burned);
+    assert_eq!(
+        TokenClient::new(&env, &token).balance(&user),
+        90_990 * SCALAR_7
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #424)")] // InvalidConfiguration
+fn test_exit_fee_of_100_percent_fails() {
+    let (env, vault, _, _, _) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7),
+        duration: 86_400,
+    });
+}
+
+#[test]
+fn test_governor_controls_fee_parameters() {
+    let (env, vault, _, _, strategy) = setup_test();
+    let governor = Address::generate(&env);
+    assert_eq!(vault.governor(), vault.admin());
+
+    vault.set_governor(&governor);
+    assert_eq!(
+        decoded_events(&env, &vault.address),
+        std::vec![VaultEvent::GovernorSet(client::GovernorSetEvent {
+            governor: ScAddress::from(&governor),
+        })]
+    );
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    assert_eq!(env.auths()[0].0, governor);
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 10));
+    assert_eq!(env.auths()[0].0, governor);
+}
+
+// ==================== Stats Tests ====================
+
+#[test]
+fn test_stats_track_lifetime_flows() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    vault.set_profit_share(&strategy, &(SCALAR_7 / 5)); // 20%
+    vault.set_borrow_fee(&strategy, &(SCALAR_7 / 100)); // 1%
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(1000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(510 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(1500 * SCALAR_7));
+    let assets = vault.withdraw(&(990 * SCALAR_7), &user, &user, &user);
+    assert!(assets > 0);
+
+    assert_eq!(
+        vault.get_stats(),
+        VaultStats {
+            total_deposited: 11_000 * SCALAR_7,
+            total_withdrawn: 990 * SCALAR_7,
+            exit_fees: 10 * SCALAR_7,
+            strategy_fees: 100 * SCALAR_7,
+            borrow_fees: 10 * SCALAR_7,
+            depositors: 1,
+        }
+    );
+}
+
+#[test]
+fn test_redemption_history_keeps_latest_exits() {
+    let (env, vault, _, user, _) = setup_test();
+
+    vault.set_exit_fee(&ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 100), // 1%
+        duration: 86_400,
+    });
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let assets = vault.redeem(&(1000 * SCALAR_7), &user, &user, &user);
+    assert_eq!(
+        vault.redemption_history(&user),
+        Vec::from_array(
+            &env,
+            [RedemptionRecord {
+                shares: 1000 * SCALAR_7,
+                assets,
+                exit_fee: 10 * SCALAR_7,
+                timestamp: env.ledger().timestamp(),
+            }]
+        )
+    );
+
+    for _ in 0..REDEMPTION_HISTORY_LEN {
+        vault.redeem(&SCALAR_7, &user, &user, &user);
+    }
+    let history = vault.redemption_history(&user);
+    assert_eq!(history.len(), REDEMPTION_HISTORY_LEN);
+    assert!(history.iter().all(|record| record.shares == SCALAR_7));
+    assert_eq!(
+        vault.balance(&user),
+        shares - 1000 * SCALAR_7 - i128::from(REDEMPTION_HISTORY_LEN) * SCALAR_7
+    );
+}
+
+#[test]
+fn test_user_pnl_tracks_cost_basis_and_realized_gain() {
+    let (env, vault, _, user, _) = setup_test();
+    let sponsor = Address::generate(&env);
+    let other = Address::generate(&env);
+    StellarAssetClient::new(&env, &vault.query_asset()).mint(&sponsor, &(1000 * SCALAR_7));
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.donate(&sponsor, &(1000 * SCALAR_7));
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME + 1);
+
+    let shares = vault.balance(&user) / 2;
+    let assets = vault.redeem(&shares, &user, &user, &user);
+    assert!(assets > 5000 * SCALAR_7);
+    assert_eq!(
+        vault.user_pnl(&user),
+        UserPnl {
+            cost_basis: 5000 * SCALAR_7,
+            realized: assets - 5000 * SCALAR_7,
+        }
+    );
+
+    // Transferred shares carry their cost basis to the recipient
+    vault.transfer(&user, &other, &(vault.balance(&user) / 2));
+    assert_eq!(vault.user_pnl(&user).cost_basis, 2500 * SCALAR_7);
+    assert_eq!(
+        vault.user_pnl(&other),
+        UserPnl {
+            cost_basis: 2500 * SCALAR_7,
+            realized: 0,
+        }
+    );
+}
+
+#[test]
+fn test_accounting_snapshot_reports_full_state() {
+    let (env, vault, token, user, strategy) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    vault.strategy_withdraw(&strategy, &(4000 * SCALAR_7));
+    StellarAssetClient::new(&env, &token).mint(&strategy, &(1000 * SCALAR_7));
+    vault.strategy_deposit(&strategy, &(1000 * SCALAR_7));
+
+    assert_eq!(
+        vault.accounting_snapshot(),
+        AccountingSnapshot {
+            total_supply: 10_000 * SCALAR_7,
+            total_assets: 7000 * SCALAR_7,
+            deployed: 3000 * SCALAR_7,
+            net_impact: -3000 * SCALAR_7,
+            share_price: vault.convert_to_assets(&SCALAR_7),
+            timestamp: env.ledger().timestamp(),
+        }
+    );
+}
+
+#[test]
+fn test_asset_info_describes_both_tokens() {
+    let (env, vault, token, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    StellarAssetClient::new(&env, &token).mint(&vault.address, &(1000 * SCALAR_7));
+
+    assert_eq!(
+        vault.asset_info(),
+        AssetInfo {
+            asset: token.clone(),
+            asset_decimals: TokenClient::new(&env, &token).decimals(),
+            share_token: vault.address.clone(),
+            share_name: vault.name(),
+            share_symbol: vault.symbol(),
+            share_decimals: vault.decimals(),
+            share_price: vault.convert_to_assets(&SCALAR_7),
+        }
+    );
+    assert!(vault.asset_info().share_price > SCALAR_7);
+}
+
+// ==================== Reference Model Tests ====================
+
+/// Plain-Rust model of the vault's share accounting with `decimals_offset` 0
+#[derive(Default)]
+struct ReferenceVault {
+    total_assets: i128,
+    total_supply: i128,
+    balances: [i128; 3],
+}
+
+impl ReferenceVault {
+    fn to_shares(&self, assets: i128, round_up: bool) -> i128 {
+        let (num, den) = (assets * (self.total_supply + 1), self.total_assets + 1);
+        if round_up {
+            (num + den - 1) / den
+        } else {
+            num / den
+        }
+    }
+
+    fn to_assets(&self, shares: i128, round_up: bool) -> i128 {
+        let (num, den) = (shares * (self.total_assets + 1), self.total_supply + 1);
+        if round_up {
+            (num + den - 1) / den
+        } else {
+            num / den
+        }
+    }
+
+    fn deposit(&mut self, user: usize, assets: i128) -> i128 {
+        let mut shares = self.to_shares(assets, false);
+        if self.total_supply == 0 {
+            shares -= BOOTSTRAP_SHARES;
+            self.total_supply += BOOTSTRAP_SHARES;
+        }
+        self.total_assets += assets;
+        self.total_supply += shares;
+        self.balances[user] += shares;
+        shares
+    }
+
+    fn mint(&mut self, user: usize, shares: i128) -> i128 {
+        let assets = self.to_assets(shares, true);
+        self.total_assets += assets;
+        self.total_supply += shares;
+        self.balances[user] += shares;
+        assets
+    }
+
+    fn redeem(&mut self, user: usize, shares: i128) -> i128 {
+        let assets = self.to_assets(shares, false);
+        self.total_assets -= assets;
+        self.total_supply -= shares;
+        self.balances[user] -= shares;
+        assets
+    }
+
+    fn withdraw(&mut self, user: usize, assets: i128) -> i128 {
+        let shares = self.to_shares(assets, true);
+        self.total_assets -= assets;
+        self.total_supply -= shares;
+        self.balances[user] -= shares;
+        shares
+    }
+}
+
+#[test]
+fn test_vault_matches_reference_model() {
+    let (env, vault, token, user, strategy) = setup_test();
+    let users = [user, Address::generate(&env), Address::generate(&env)];
+    for user in &users[1..] {
+        StellarAssetClient::new(&env, &token).mint(user, &(100_000 * SCALAR_7));
+    }
+    let mut model = ReferenceVault::default();
+
+    // Deterministic xorshift so failures replay identically
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = |bound: i128| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as i128
+    };
+
+    let shares = vault.deposit(&(10_000 * SCALAR_7), &users[0], &users[0], &users[0]);
+    assert_eq!(shares, model.deposit(0, 10_000 * SCALAR_7));
+
+    for _ in 0..60 {
+        let i = next(3) as usize;
+        let user = &users[i];
+        match next(6) {
+            0 => {
+                let assets = 1 + next(1000 * SCALAR_7);
+                let shares = vault.deposit(&assets, user, user, user);
+                assert_eq!(shares, model.deposit(i, assets));
+            }
+            1 => {
+                let shares = 1 + next(1000 * SCALAR_7);
+                let assets = vault.mint(&shares, user, user, user);
+                assert_eq!(assets, model.mint(i, shares));
+            }
+            2 if model.balances[i] > 0 => {
+                let shares = 1 + next(model.balances[i]);
+                let assets = vault.redeem(&shares, user, user, user);
+                assert_eq!(assets, model.redeem(i, shares));
+            }
+            3 if model.balances[i] > 0 => {
+                let max = model.to_assets(model.balances[i], false);
+                if max == 0 {
+                    continue;
+                }
+                let assets = 1 + next(max);
+                let shares = vault.withdraw(&assets, user, user, user);
+                assert_eq!(shares, model.withdraw(i, assets));
+            }
+            4 => {
+                let amount = 1 + next(model.total_assets / 4);
+                vault.strategy_withdraw(&strategy, &amount);
+                model.total_assets -= amount;
+            }
+            5 => {
+                let amount = 1 + next(500 * SCALAR_7);
+                StellarAssetClient::new(&env, &token).mint(&strategy, &amount);
+                vault.strategy_deposit(&strategy, &amount);
+                model.total_assets += amount;
+            }
+            _ => continue,
+        }
+
+        assert_eq!(vault.total_assets(), model.total_assets);
+        assert_eq!(vault.total_supply(), model.total_supply);
+        for (user, balance) in users.iter().zip(model.balances) {
+            assert_eq!(vault.balance(user), balance);
+        }
+    }
+}
+
+// ==================== Rounding Tests ====================
+
+#[test]
+fn test_rounding_info_matches_previews() {
+    let (env, vault, token, user, _) = setup_test();
+
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    StellarAssetClient::new(&env, &token).mint(&user, &1_234_567);
+    vault.donate(&user, &1_234_567);
+
+    let info = vault.rounding_info();
+    assert_eq!(info, ROUNDING);
+    // At a non-integer share price, floor and ceil differ by one
+    let assets = 999_999;
+    let exact_shares = assets * (vault.total_supply() + 1) / (vault.total_assets() + 1);
+    assert_eq!(info.deposit, Rounding::Floor);
+    assert_eq!(vault.preview_deposit(&assets), exact_shares);
+    assert_eq!(info.withdraw, Rounding::Ceil);
+    assert_eq!(vault.preview_withdraw(&assets), exact_shares + 1);
+}
+
+#[test]
+fn test_no_pair_of_operations_gains_from_rounding() {
+    let amounts = [1, 7, SCALAR_7 - 1, 123_456_789, 10_000 * SCALAR_7 + 3];
+    for donation in [0, 1, 3 * SCALAR_7 + 7, 12_345_678_901] {
+        let (env, vault, token, user, _) = setup_test();
+        let trader = Address::generate(&env);
+        let asset = TokenClient::new(&env, &token);
+        StellarAssetClient::new(&env, &token).mint(&trader, &(100_000 * SCALAR_7));
+        vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+        if donation > 0 {
+            StellarAssetClient::new(&env, &token).mint(&user, &donation);
+            vault.donate(&user, &donation);
+        }
+
+        for amount in amounts {
+            // Previews: every round trip returns at most what went in
+            assert!(vault.preview_redeem(&vault.preview_deposit(&amount)) <= amount);
+            assert!(vault.preview_redeem(&amount) <= vault.preview_mint(&amount));
+            assert!(vault.preview_redeem(&vault.preview_withdraw(&amount)) >= amount);
+            assert!(vault.preview_deposit(&amount) <= vault.preview_withdraw(&amount));
+
+            // Executed pairs: the trader never ends up with more assets
+            let before = asset.balance(&trader);
+            if vault.preview_deposit(&amount) > 0 {
+                let shares = vault.deposit(&amount, &trader, &trader, &trader);
+                vault.redeem(&shares, &trader, &trader, &trader);
+                assert!(asset.balance(&trader) <= before);
+            }
+            let paid = vault.mint(&amount, &trader, &trader, &trader);
+            let burned = vault.withdraw(&vault.max_withdraw(&trader), &trader, &trader, &trader);
+            assert!(burned <= amount);
+            assert!(asset.balance(&trader) <= before);
+            assert!(paid >= vault.preview_redeem(&amount));
+            if vault.balance(&trader) > 0 {
+                vault.redeem(&vault.balance(&trader), &trader, &trader, &trader);
+            }
+            assert!(asset.balance(&trader) <= before);
+        }
+    }
+}
+
+#[test]
+fn test_rate_setters_reject_out_of_range_rates() {
+    let (env, vault, _, _, strategy) = setup_test();
+    let invalid = Some(Ok(StrategyVaultError::InvalidConfiguration.into()));
+
+    for rate in [-1, SCALAR_7 + 1] {
+        assert_eq!(vault.try_set_profit_share(&strategy, &rate).err(), invalid);
+        assert_eq!(vault.try_set_borrow_fee(&strategy, &rate).err(), invalid);
+        assert_eq!(vault.try_set_max_deployed_rate(&rate).err(), invalid);
+        assert_eq!(vault.try_set_max_price_change(&rate).err(), invalid);
+    }
+    // An exit fee arrives as a contract argument, so its rate is checked on write too
+    let rate = Rate::try_from_val(&env, &Vec::from_array(&env, [-1_i128]).to_val()).unwrap();
+    let fee = ExitFee {
+        rate,
+        duration: 86_400,
+    };
+    assert_eq!(vault.try_set_exit_fee(&fee).err(), invalid);
+    // Fees must leave something once they are taken
+    assert_eq!(
+        vault.try_set_borrow_fee(&strategy, &SCALAR_7).err(),
+        invalid
+    );
+
+    vault.set_profit_share(&strategy, &SCALAR_7);
+    vault.set_borrow_fee(&strategy, &(SCALAR_7 - 1));
+    vault.set_max_deployed_rate(&0);
+    vault.set_max_price_change(&SCALAR_7);
+    assert_eq!(vault.get_strategy(&strategy).profit_share, Rate::MAX);
+    assert_eq!(vault.max_price_change(), SCALAR_7);
+}
+
+#[test]
+fn test_rate_math_rounds_in_the_vaults_favor() {
+    let env = Env::default();
+    let third = Rate::new(&env, SCALAR_7 / 3);
+
+    // Fees round down
+    assert_eq!(third.of(&env, 10), 3);
+    assert_eq!(third.of(&env, 2), 0);
+    assert_eq!(Rate::MAX.of(&env, 10), 10);
+    // The gross-up rounds up and always leaves at least the net amount
+    assert_eq!(third.gross_up(&env, 2), 3);
+    assert_eq!(Rate::default().gross_up(&env, 5), 5);
+    for net in [1, 7, SCALAR_7 - 1, 123_456_789, 10_000 * SCALAR_7 + 3] {
+        let gross = third.gross_up(&env, net);
+        assert!(gross - third.of(&env, gross) >= net);
+    }
+    // Limits are exceeded only strictly past the fraction
+    let tenth = Rate::new(&env, SCALAR_7 / 10);
+    assert!(!tenth.is_exceeded(&env, 10, 100));
+    assert!(tenth.is_exceeded(&env, 11, 100));
+    assert!(!Rate::MAX.is_exceeded(&env, 100, 100));
+    // Decay rounds down and never exceeds the full rate
+    assert_eq!(Rate::new(&env, 3).scale(&env, 1, 2).value(), 1);
+    assert_eq!(third.scale(&env, 86_400, 86_400), third);
+    assert_eq!(third.scale(&env, 0, 86_400), Rate::default());
+}
+
+#[test]
+fn test_rate_math_handles_amounts_at_the_i128_bound() {
+    let env = Env::default();
+    let half = Rate::new(&env, SCALAR_7 / 2);
+
+    // The intermediate products overflow an i128, the results do not
+    assert_eq!(Rate::MAX.of(&env, i128::MAX), i128::MAX);
+    assert_eq!(half.of(&env, i128::MAX), i128::MAX / 2);
+    assert!(!Rate::MAX.is_exceeded(&env, i128::MAX, i128::MAX));
+    assert!(half.is_exceeded(&env, i128::MAX / 2 + 1, i128::MAX));
+    assert_eq!(Rate::default().gross_up(&env, i128::MAX / 2), i128::MAX / 2);
+    assert_eq!(half.scale(&env, u64::MAX, u64::MAX), half);
+}
+
+#[test]
+#[should_panic]
+fn test_rate_gross_up_past_the_i128_bound_panics() {
+    let env = Env::default();
+    Rate::new_fee(&env, SCALAR_7 / 2).gross_up(&env, i128::MAX / 2 + 1);
+}
+
+proptest! {
+    #[test]
+    fn prop_rate_of_is_the_floored_fraction(
+        rate in 0..=SCALAR_7,
+        amount in 0..=i128::MAX,
+    ) {
+        let env = Env::default();
+        let fee = Rate::new(&env, rate).of(&env, amount);
+
+        prop_assert!((0..=amount).contains(&fee));
+        match amount.checked_mul(rate) {
+            Some(product) => prop_assert_eq!(fee, product / SCALAR_7),
+            // Split the amount so the reference stays within an i128
+            None => prop_assert_eq!(
+                fee,
+                amount / SCALAR_7 * rate + amount % SCALAR_7 * rate / SCALAR_7
+            ),
+        }
+    }
+
+    #[test]
+    fn prop_rate_gross_up_is_the_least_amount_covering_the_exact_fee(
+        rate in 0..SCALAR_7,
+        net in 0..=i128::MAX / SCALAR_7,
+    ) {
+        let env = Env::default();
+        let fee = Rate::new_fee(&env, rate);
+        let gross = fee.gross_up(&env, net);
+
+        prop_assert!(gross >= net);
+        prop_assert!(gross - fee.of(&env, gross) >= net);
+        prop_assert!(gross * (SCALAR_7 - rate) >= net * SCALAR_7);
+        if gross > 0 {
+            prop_assert!((gross - 1) * (SCALAR_7 - rate) < net * SCALAR_7);
+        }
+    }
+
+    #[test]
+    fn prop_rate_scale_stays_within_the_rate(
+        rate in 0..=SCALAR_7,
+        denominator in 1..=u64::MAX,
+        numerator_seed in 0..=u64::MAX,
+    ) {
+        let env = Env::default();
+        let rate = Rate::new(&env, rate);
+        let numerator = numerator_seed % denominator;
+        let scaled = rate.scale(&env, numerator, denominator);
+
+        prop_assert!((0..=rate.value()).contains(&scaled.value()));
+        prop_assert!(scaled.value() <= rate.scale(&env, numerator + 1, denominator).value());
+        prop_assert_eq!(rate.scale(&env, denominator, denominator), rate);
+    }
+
+    #[test]
+    fn prop_rate_is_exceeded_only_past_the_fraction(
+        rate in 0..=SCALAR_7,
+        whole in 0..=i128::MAX / SCALAR_7,
+        part in 0..=i128::MAX / SCALAR_7,
+    ) {
+        let env = Env::default();
+        prop_assert_eq!(
+            Rate::new(&env, rate).is_exceeded(&env, part, whole),
+            part * SCALAR_7 > rate * whole
+        );
+    }
+}
+
+// ==================== Golden Vector Tests ====================
+
+const GOLDEN_VECTORS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test_vectors/golden.json");
+
+/// Formats `(input, output)` pairs as a JSON array; amounts are strings so JS
+/// clients don't lose precision
+fn json_pairs(pairs: &[(i128, i128)]) -> std::string::String {
+    let pairs: std::vec::Vec<_> = pairs
+        .iter()
+        .map(|(input, output)| std::format!("[\"{input}\", \"{output}\"]"))
+        .collect();
+    std::format!("[{}]", pairs.join(", "))
+}
+
+/// Runs the vault's preview and exit fee math for fixed inputs and renders the
+/// results as JSON
+fn golden_vectors() -> std::string::String {
+    let amounts = [1, 999, SCALAR_7, 123_456_789_012];
+    let mut states = std::vec::Vec::new();
+    for (deposit, donation) in [
+        (10_000 * SCALAR_7, 0),
+        (10_000 * SCALAR_7, 1_234_567_890),
+        (SCALAR_7, 77_777 * SCALAR_7),
+    ] {
+        let (env, vault, token, user, _) = setup_test();
+        vault.deposit(&deposit, &user, &user, &user);
+        if donation > 0 {
+            StellarAssetClient::new(&env, &token).mint(&user, &donation);
+            vault.donate(&user, &donation);
+        }
+        let preview =
+            |f: &dyn Fn(&i128) -> i128| json_pairs(&amounts.map(|amount| (amount, f(&amount))));
+        states.push(std::format!(
+            "    {{\n      \"total_assets\": \"{}\",\n      \"total_supply\": \"{}\",\n      \
+             \"preview_deposit\": {},\n      \"preview_mint\": {},\n      \
+             \"preview_redeem\": {},\n      \"preview_withdraw\": {}\n    }}",
+            vault.total_assets(),
+            vault.total_supply(),
+            preview(&|a| vault.preview_deposit(a)),
+            preview(&|s| vault.preview_mint(s)),
+            preview(&|s| vault.preview_redeem(s)),
+            preview(&|a| vault.preview_withdraw(a)),
+        ));
+    }
+
+    // Exit fee charged on a fixed redemption as the fee decays
+    let (env, vault, _, user, _) = setup_test();
+    let fee = ExitFee {
+        rate: Rate::new(&env, SCALAR_7 / 50), // 2%
+        duration: 86_400,
+    };
+    vault.set_exit_fee(&fee);
+    vault.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    let start = env.ledger().timestamp();
+    let mut exits = std::vec::Vec::new();
+    for elapsed in [0, 1, 21_600, 43_200, 86_399, 86_400] {
+        env.ledger().set_timestamp(start + elapsed);
+        let shares = 1_234_567;
+        let gross = vault.preview_redeem(&shares);
+        let rate = vault.exit_fee_rate(&user);
+        let assets = vault.redeem(&shares, &user, &user, &user);
+        exits.push(std::format!(
+            "    {{ \"elapsed\": {elapsed}, \"rate\": \"{rate}\", \"shares\": \"{shares}\", \
+             \"gross\": \"{gross}\", \"assets\": \"{assets}\" }}"
+        ));
+    }
+
+    std::format!(
+        "{{\n  \"decimals_offset\": 0,\n  \"bootstrap_shares\": \"{BOOTSTRAP_SHARES}\",\n  \
+         \"states\": [\n{}\n  ],\n  \"exit_fee\": {{\n    \"rate\": \"{}\",\n    \
+         \"duration\": {},\n    \"exits\": [\n{}\n    ]\n  }}\n}}\n",
+        states.join(",\n"),
+        fee.rate.value(),
+        fee.duration,
+        exits
+            .iter()
+            .map(|exit| std::format!("  {exit}"))
+            .collect::<std::vec::Vec<_>>()
+            .join(",\n"),
+    )
+}
+
+/// Checks the contract against the published vectors. Run with `UPDATE_GOLDEN=1` to
+/// regenerate `test_vectors/golden.json` after an intended change to the math.
+#[test]
+fn test_golden_vectors_are_up_to_date() {
+    let vectors = golden_vectors();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(std::path::Path::new(GOLDEN_VECTORS).parent().unwrap()).unwrap();
+        std::fs::write(GOLDEN_VECTORS, &vectors).unwrap();
+    }
+    assert_eq!(std::fs::read_to_string(GOLDEN_VECTORS).unwrap(), vectors);
+}
+
+// ==================== Vault-of-Vaults Tests ====================
+
+#[test]
+fn test_base_asset_of_plain_vault_is_underlying() {
+    let (_env, vault, token, user, _) = setup_test();
+
+    vault.deposit(&(1000 * SCALAR_7), &user, &user, &user);
+
+    assert_eq!(vault.base_asset(), token);
+    assert_eq!(
+        vault.convert_to_base_assets(&(100 * SCALAR_7)),
+        vault.convert_to_assets(&(100 * SCALAR_7))
+    );
+}
+
+#[test]
+fn test_vault_of_vaults_chains_conversions() {
+    let (env, inner, token, user, strategy) = setup_test();
+    let outer_address = register_vault(&env, &inner.admin(), &inner.address, Vec::new(&env));
+    let outer = StrategyVaultContractClient::new(&env, &outer_address);
+
+    let inner_shares = inner.deposit(&(10_000 * SCALAR_7), &user, &user, &user);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + LOCK_TIME + 1);
+    let outer_shares = outer.deposit(&inner_shares, &user, &user, &user);
+    assert_eq!(outer.total_assets(), inner_shares);
+
+    // Inner vault loses value to its strategy; the outer price follows
+    inner.strategy_withdraw(&strategy, &(2000 * SCALAR_7));
+
+    assert_eq!(outer.base_asset(), token);
+    let expected = inner.convert_to_assets(&outer.convert_to_assets(&outer_shares));
+    assert_eq!(outer.convert_to_base_assets(&outer_shares), expected);
+    assert!(expected > 7999 * SCALAR_7 && expected <= 8000 * SCALAR_7);
+
+    let inner_back = outer.redeem(&outer_shares, &user, &user, &user);
+    assert_eq!(inner.balance(&user), inner_back);
 }